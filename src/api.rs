@@ -1,240 +1,206 @@
-use std::sync::{Arc, OnceLock};
+use std::sync::Arc;
 
-use binance::{
-    account::Account,
-    api::Binance,
-    market::Market,
-    rest_model::{OrderSide, OrderStatus},
-};
 use iced::Command;
-use iced_futures::futures::future::join_all;
-use regex::Regex;
 
-use crate::message::Message;
+use crate::{
+    data::ChartKey,
+    exchange::{BinanceSpot, Exchange, OcoRequest, Order, OrderRequest, OrderType, TimeInForce},
+    message::Message,
+};
 
-static SPLIT_SYMBOL_REGEX: OnceLock<Regex> = OnceLock::new();
+/// Timeframe of newly opened charts
+pub(crate) const DEFAULT_TIMEFRAME: &str = "5m";
 
 pub(crate) struct Client {
-    binance_account: Arc<Account>,
-    binance_market: Arc<Market>,
+    exchange: Arc<dyn Exchange>,
 }
 
 impl Client {
-    fn make_client(public: String, secret: String) -> Arc<Account> {
-        Arc::new(Binance::new(Some(public), Some(secret)))
-    }
-
-    fn make_market(public: String, secret: String) -> Arc<Market> {
-        Arc::new(Binance::new(Some(public), Some(secret)))
+    fn make_exchange(public: String, secret: String) -> Arc<dyn Exchange> {
+        Arc::new(BinanceSpot::new(public, secret))
     }
 
     pub(crate) fn new(public: String, secret: String) -> Self {
         Self {
-            binance_account: Self::make_client(public.clone(), secret.clone()),
-            binance_market: Self::make_market(public, secret),
+            exchange: Self::make_exchange(public, secret),
         }
     }
 
     /// Replace credentials in inner client
     pub(crate) fn update_credentials(&mut self, public: String, secret: String) {
-        self.binance_account = Self::make_client(public, secret);
+        self.exchange = Self::make_exchange(public, secret);
     }
 
     pub(crate) fn orders_history(&self, assets: Vec<String>) -> Command<Message> {
-        let binance_account = Arc::clone(&self.binance_account);
+        let now = chrono::offset::Local::now();
+        let ago = now
+            .checked_sub_signed(chrono::Duration::try_weeks(8).unwrap())
+            .unwrap();
+
+        let requested_at = now.timestamp_millis() as u64;
 
         Command::perform(
-            async move {
-                let now = chrono::offset::Local::now();
-                let ago = now
-                    .checked_sub_signed(chrono::Duration::try_weeks(8).unwrap())
-                    .unwrap();
-                let mut os: Vec<_> = {
-                    join_all(assets.into_iter().map(|a| {
-                        binance_account.get_all_orders(binance::account::OrdersQuery {
-                            symbol: a,
-                            order_id: None,
-                            start_time: Some(ago.timestamp_millis() as u64),
-                            end_time: None,
-                            limit: None,
-                            recv_window: None,
-                        })
-                    }))
-                    .await
-                    .into_iter()
-                    .flatten()
-                    .flatten()
-                    .filter(|o| {
-                        matches!(o.status, OrderStatus::Filled | OrderStatus::PartiallyFilled)
-                    })
-                    .collect()
-                };
-
-                os.sort_by(|o, n| n.time.cmp(&o.time));
-                os
+            self.exchange
+                .orders_history(assets, ago.timestamp_millis() as u64),
+            move |results| {
+                let (mut symbols, mut orders, mut failed) = (Vec::new(), Vec::new(), Vec::new());
+
+                for (symbol, result) in results {
+                    match result {
+                        // canceled orders count too, if they were partially filled
+                        Ok(history) => {
+                            orders.extend(history.into_iter().filter(|o| o.executed_qty > 0.0));
+                            symbols.push(symbol);
+                        }
+                        Err(e) => failed.push((symbol, e.to_string())),
+                    }
+                }
+
+                Message::OrdersRecieved {
+                    symbols,
+                    orders,
+                    failed,
+                    requested_at,
+                }
             },
-            Message::OrdersRecieved,
         )
     }
 
-    pub(crate) fn balances(&self, assets: Vec<String>) -> Command<Message> {
-        let binance_account = Arc::clone(&self.binance_account);
+    pub(crate) fn balances(&self) -> Command<Message> {
+        Command::perform(self.exchange.balances(), |result| match result {
+            Ok(balances) => Message::BalancesRecieved(balances),
+            Err(e) => Message::DispatchErr(("balances".to_string(), e.to_string())),
+        })
+    }
 
-        Command::perform(
-            async move {
-                join_all(assets.iter().map(|a| binance_account.get_balance(a)))
-                    .await
-                    .into_iter()
-                    .flatten()
-                    .collect()
+    pub(crate) fn klines(&self, key: ChartKey) -> Command<Message> {
+        let klines = self
+            .exchange
+            .klines(key.symbol.clone(), key.timeframe.clone(), 500, None);
+
+        Command::perform(klines, |result| match result {
+            Ok(klines) => Message::KlinesRecieved { key, klines },
+            Err(e) => Message::KlinesFailed {
+                key,
+                error: e.to_string(),
             },
-            Message::BalancesRecieved,
-        )
+        })
+    }
+
+    /// Klines opened before `open_time` of oldest loaded one
+    pub(crate) fn older_klines(&self, key: ChartKey, open_time: i64) -> Command<Message> {
+        let end_time = u64::try_from(open_time - 1).unwrap_or_default();
+        let klines = self.exchange.klines(
+            key.symbol.clone(),
+            key.timeframe.clone(),
+            500,
+            Some(end_time),
+        );
+
+        Command::perform(klines, |result| match result {
+            Ok(klines) => Message::OlderKlinesRecieved { key, klines },
+            Err(e) => Message::DispatchErr(("klines".to_string(), e.to_string())),
+        })
+    }
+
+    pub(crate) fn open_orders(&self) -> Command<Message> {
+        let requested_at = chrono::offset::Local::now().timestamp_millis() as u64;
+
+        Command::perform(self.exchange.open_orders(), move |result| match result {
+            Ok(orders) => Message::OpenOrdersRecieved {
+                orders,
+                requested_at,
+            },
+            Err(e) => Message::DispatchErr(("open orders".to_string(), e.to_string())),
+        })
     }
 
-    pub(crate) fn klines(&self, pair: String, tf: String) -> Command<Message> {
-        let market = Arc::clone(&self.binance_market);
+    pub(crate) fn cancel_order(&self, symbol: String, order_id: u64) -> Command<Message> {
         Command::perform(
-            async move {
-                let acc = market;
-                acc.get_klines(
-                    pair,
-                    if tf.is_empty() { "5m" } else { &tf },
-                    500,
-                    None,
-                    None,
-                )
-                .await
-                .unwrap()
+            self.exchange.cancel_order(symbol.clone(), order_id),
+            move |result| match result {
+                Ok(order_id) => Message::OrdersCanceled(vec![(symbol, order_id)]),
+                Err(e) => Message::DispatchErr(("cancel order".to_string(), e.to_string())),
             },
-            Message::KlinesRecieved,
         )
     }
 
-    pub(crate) fn trade_spot(
-        &self,
-        pair: String,
-        price: f64,
-        amt: f64,
-        side: OrderSide,
-    ) -> Command<Message> {
-        let binance_account = Arc::clone(&self.binance_account);
-
+    pub(crate) fn cancel_all_orders(&self, symbol: String) -> Command<Message> {
         Command::perform(
-            async move {
-                binance_account
-                    .place_order(binance::account::OrderRequest {
-                        symbol: pair,
-                        side,
-                        order_type: binance::rest_model::OrderType::Limit,
-                        time_in_force: Some(binance::rest_model::TimeInForce::GTC),
-                        quantity: Some(amt),
-                        quote_order_qty: None,
-                        price: Some(price),
-                        new_client_order_id: None,
-                        stop_price: None,
-                        iceberg_qty: None,
-                        new_order_resp_type: None,
-                        recv_window: None,
-                    })
-                    .await
-            },
-            |m| {
-                println!("{m:?}");
-                Message::MarketChanged("REEEEE".to_string())
+            self.exchange.cancel_all_orders(symbol.clone()),
+            move |result| match result {
+                Ok(order_ids) => Message::OrdersCanceled(
+                    order_ids
+                        .into_iter()
+                        .map(|order_id| (symbol.clone(), order_id))
+                        .collect(),
+                ),
+                Err(e) => Message::DispatchErr(("cancel all orders".to_string(), e.to_string())),
             },
         )
     }
 
-    pub(crate) fn split_symbol(symbol: &str) -> Option<[&str; 2]> {
-        let regex = SPLIT_SYMBOL_REGEX.get_or_init(|| {
-            let quote_assets = vec![
-                "BTC", "ETH", "USDT", "BNB", "TUSD", "PAX", "USDC", "XRP", "USDS", "TRX", "BUSD",
-                "NGN", "RUB", "TRY", "EUR", "ZAR", "BKRW", "IDRT", "GBP", "UAH", "BIDR", "AUD",
-                "DAI", "BRL", "BVND", "VAI", "USDP", "DOGE", "UST", "DOT", "PLN", "RON", "ARS",
-            ];
+    pub(crate) fn symbols(&self) -> Command<Message> {
+        Command::perform(self.exchange.symbols(), |result| match result {
+            Ok(symbols) => Message::SymbolsRecieved(symbols),
+            Err(e) => Message::DispatchErr(("exchange info".to_string(), e.to_string())),
+        })
+    }
 
-            let quote_assets_regex = quote_assets.join("|");
-            Regex::new(&format!(r"^([0-9A-Z]+)({quote_assets_regex})$")).unwrap()
-        });
+    pub(crate) fn trade_spot(&self, request: OrderRequest) -> Command<Message> {
+        let symbol = request.symbol.clone();
 
-        regex.captures(symbol).map(|captures| captures.extract().1)
+        Command::perform(self.exchange.place_order(request), |result| match result {
+            Ok(order) => Message::OrderPlaced(vec![order]),
+            Err(error) => Message::OrderRejected { symbol, error },
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn split_base_qty() {
-        let test_cases = vec![
-            "BTCUSDT",
-            "ETHBTC",
-            "XRPUSDC",
-            "LTCETH",
-            "BNBTUSD",
-            "TRXBUSD",
-            "ZRXUSDT",
-            "INVALIDMARKET",
-            "BTCUSDC",
-            "ETHUSDT",
-            "BNBETH",
-            "XRPBTC",
-            "LTCUSDT",
-            "DOTUSDT",
-            "DOGEUSDT",
-            "USDTUSD",
-            "BTCTUSD",
-            "BTCPAX",
-            "BTCUSDS",
-            "BTCNGN",
-            "BTCRUB",
-            "BTCTRY",
-            "BTCEUR",
-            "BTCZAR",
-            "BTCBKRW",
-            "BTCIDRT",
-            "ETHGBP",
-            "ETHUAH",
-            "ETHBIDR",
-            "ETHAUD",
-            "ETHDAI",
-            "ETHBRL",
-            "ETHBVND",
-            "USDTDAI",
-            "USDCUSDT",
-            "USDTBRL",
-            "BNBBUSD",
-            "BTCBRL",
-            "BTCVAI",
-            "BUSDUSDT",
-            "BTCUSDP",
-            "BTCDOT",
-            "ETHUST",
-            "BTCUST",
-            "BTCPLN",
-            "BTCRON",
-            "BTCARS",
-            "1INCHUSDT",
-        ];
-
-        for symbol in test_cases {
-            match Client::split_symbol(symbol) {
-                Some([base, quote]) => {
-                    assert_eq!(
-                        format!("{base}{quote}"),
-                        symbol,
-                        "Split symbol should recombine to the original symbol"
-                    );
+    /// Move resting limit order to `price`, exchange has no amend so it is canceled and placed again
+    ///
+    /// Steps are not atomic, so rejected replacement is reported apart from canceled original.
+    pub(crate) fn replace_order(&self, order: Order, price: f64) -> Command<Message> {
+        let exchange = Arc::clone(&self.exchange);
+        let (symbol, old_id) = (order.symbol.clone(), order.order_id);
+        let request = OrderRequest {
+            symbol: order.symbol.clone(),
+            side: order.side,
+            order_type: order.order_type,
+            time_in_force: (order.order_type == OrderType::Limit).then_some(TimeInForce::Gtc),
+            price: Some(price),
+            stop_price: None,
+            qty: order.orig_qty - order.executed_qty,
+        };
+
+        Command::perform(
+            async move {
+                // order stays where it was, so failure is not reported as rejection
+                if let Err(e) = exchange.cancel_order(symbol.clone(), old_id).await {
+                    return Message::DispatchErr((
+                        "move order".to_string(),
+                        format!("{symbol}: {e}"),
+                    ));
                 }
-                None => {
-                    assert_eq!(
-                        symbol, "INVALIDMARKET",
-                        "Invalid market symbol should be INVALIDMARKET"
-                    );
+
+                match exchange.place_order(request).await {
+                    Ok(order) => Message::OrderReplaced { old_id, order },
+                    Err(error) => Message::ReplacementRejected {
+                        symbol,
+                        old_id,
+                        error,
+                    },
                 }
-            }
-        }
+            },
+            |message| message,
+        )
+    }
+
+    pub(crate) fn trade_spot_oco(&self, request: OcoRequest) -> Command<Message> {
+        let symbol = request.symbol.clone();
+
+        Command::perform(self.exchange.place_oco(request), |result| match result {
+            Ok(orders) => Message::OrderPlaced(orders),
+            Err(error) => Message::OrderRejected { symbol, error },
+        })
     }
 }
@@ -2,36 +2,51 @@ use crate::api::Client;
 use crate::config::Config;
 use crate::data::AppData;
 use crate::data::PriceFilter;
+use crate::data::Severity;
+use crate::keymap::Action;
 use crate::message::MaybeError;
 use crate::message::Message;
 use crate::svg_logos;
-use crate::views::dashboard::DashboardView;
+use crate::views::components::toast::Toast;
+use crate::views::dashboard::{DashboardMessage, DashboardView};
+use crate::views::palette::{CommandPalette, PaletteEntry};
+use crate::views::panes::market::MarketPanelMessage;
 use crate::views::settings::SettingsView;
-use crate::ws::Websockets;
+use crate::ws::{Websockets, WsMessage};
 
+use std::collections::BTreeSet;
 use std::env;
 use std::time::Duration;
 
-use binance::rest_model::KlineSummaries;
+use iced::event;
 use iced::executor;
 use iced::font;
+use iced::keyboard;
 use iced::widget::button;
+use iced::widget::pane_grid;
+use iced::widget::pick_list;
 use iced::widget::scrollable;
 use iced::widget::svg;
+use iced::widget::text_input;
 use iced::widget::Row;
 use iced::widget::Space;
 use iced::widget::{column, container, row, text};
-use iced::{Application, Color, Command, Element, Length, Subscription, Theme};
-use ringbuf::Rb;
+use iced::window;
+use iced::{Application, Color, Command, Element, Event, Length, Subscription, Theme};
+
+/// How often orders are refetched to correct state missed by user stream
+const RECONCILE_PERIOD: Duration = Duration::from_secs(60);
 
 pub(crate) struct App {
     config: Config,
     data: AppData,
     api: Client,
-    errors: Vec<String>,
+    /// Markets of last order history request
+    history_symbols: BTreeSet<String>,
     settings_opened: bool,
     dashboard: DashboardView,
     settings: SettingsView,
+    palette: Option<CommandPalette>,
     ws: Websockets,
 }
 
@@ -42,53 +57,124 @@ impl App {
         let mut data = AppData::default();
         data.prices
             .set_filter(PriceFilter::Matches(config.watchlist_favorites.clone()));
+        data.streams.set_stale_after(config.reconnect.stale_after());
 
-        App {
+        let mut app = App {
             config: config.clone(),
             data,
             api,
-            errors: Vec::new(),
+            history_symbols: BTreeSet::new(),
             settings_opened: !config.complete(),
-            dashboard: DashboardView::new(),
-            ws: Websockets::new(config.api_key.clone(), "BTCUSDT"),
+            dashboard: DashboardView::new(config.layout.clone(), config.drawings.clone()),
+            ws: Websockets::new(config.api_key.clone(), config.reconnect),
             settings: SettingsView::new(config),
-        }
+            palette: None,
+        };
+        app.track_streams();
+        app
+    }
+
+    /// Keep market streams in line with markets shown on dashboard
+    fn track_streams(&mut self) {
+        self.ws.track(
+            self.dashboard.symbols(),
+            self.dashboard.chart_keys(),
+            &mut self.data,
+        );
     }
 
     fn fetch_data(&self) -> Command<Message> {
-        Command::batch([
-            self.api.orders_history(
-                vec![
-                    "LINKUSDT",
-                    "UNIUSDT",
-                    "1INCHUSDT",
-                    "OPUSDT",
-                    "ARBUSDT",
-                    "SYNUSDT",
-                ]
+        Command::batch(
+            [self.api.balances(), self.api.open_orders()]
                 .into_iter()
-                .map(ToOwned::to_owned)
-                .collect(),
-            ),
-            self.api.balances(
-                vec!["LINK", "UNI", "ARB", "OP", "SYN", "USDT", "OP"]
-                    .into_iter()
-                    .map(ToOwned::to_owned)
-                    .collect(),
-            ),
-            self.api.klines(
-                if self.data.quote.is_empty() {
-                    "BTCUSDT".into()
-                } else {
-                    self.data.quote.clone()
-                },
-                String::new(),
-            ),
-        ])
+                .chain(
+                    self.dashboard
+                        .chart_keys()
+                        .into_iter()
+                        .map(|key| self.api.klines(key)),
+                ),
+        )
+    }
+
+    /// Fetch order history of markets that joined set of traded ones
+    ///
+    /// Whole set is refreshed by `Message::Reconcile`.
+    fn refresh_history(&mut self) -> Command<Message> {
+        let symbols = self
+            .data
+            .traded_symbols(&self.config.history_quotes, &self.config.history_symbols);
+        let added: Vec<_> = symbols.difference(&self.history_symbols).cloned().collect();
+
+        self.history_symbols = symbols;
+        if added.is_empty() {
+            return Command::none();
+        }
+        self.api.orders_history(added)
     }
 
     fn toggle_settings(&mut self) {
+        let opening = !self.settings_opened;
         self.settings_opened = !(self.settings_opened && self.config.complete());
+
+        // settings save whole config, so they must start from the latest one
+        if opening {
+            self.settings = SettingsView::new(self.config.clone());
+        }
+    }
+
+    /// Watchlist market next to selected one
+    fn adjacent_symbol(&self, offset: isize) -> Option<String> {
+        let favorites = &self.config.watchlist_favorites;
+        let current = favorites.iter().position(|s| s == self.dashboard.pair());
+        let next = match current {
+            Some(i) => (i as isize + offset).rem_euclid(favorites.len() as isize) as usize,
+            None => 0,
+        };
+
+        favorites.get(next).cloned()
+    }
+
+    fn run_action(&mut self, action: Action) -> Command<Message> {
+        use pane_grid::{Axis, Direction};
+
+        let message: Message = match action {
+            Action::CommandPalette => {
+                if self.palette.take().is_some() {
+                    return Command::none();
+                }
+
+                self.palette = Some(CommandPalette::new());
+                return text_input::focus(CommandPalette::input_id());
+            }
+            Action::ToggleSettings => Message::SettingsToggled,
+            Action::FocusLeft => DashboardMessage::FocusAdjacent(Direction::Left).into(),
+            Action::FocusRight => DashboardMessage::FocusAdjacent(Direction::Right).into(),
+            Action::FocusUp => DashboardMessage::FocusAdjacent(Direction::Up).into(),
+            Action::FocusDown => DashboardMessage::FocusAdjacent(Direction::Down).into(),
+            Action::SplitHorizontal => DashboardMessage::SplitFocused(Axis::Horizontal).into(),
+            Action::SplitVertical => DashboardMessage::SplitFocused(Axis::Vertical).into(),
+            Action::ClosePane => DashboardMessage::CloseFocused.into(),
+            Action::NextSymbol | Action::PreviousSymbol => {
+                let offset = if action == Action::NextSymbol { 1 } else { -1 };
+                match self.adjacent_symbol(offset) {
+                    Some(symbol) => DashboardMessage::CurrencyPairSelected(symbol).into(),
+                    None => return Command::none(),
+                }
+            }
+            Action::QuickBuy => DashboardMessage::Market(MarketPanelMessage::BuyPressed).into(),
+            Action::QuickSell => DashboardMessage::Market(MarketPanelMessage::SellPressed).into(),
+        };
+
+        self.update(message)
+    }
+
+    /// Write config changed outside of settings
+    fn save_config(&self) -> Command<Message> {
+        let config = self.config.clone();
+
+        Command::perform(async move { config.save() }, |result| {
+            MaybeError::new("config".to_string()).maybe(&result).into()
+        })
     }
 }
 
@@ -101,11 +187,13 @@ impl Application for App {
     fn new(flags: Self::Flags) -> (Self, Command<Message>) {
         let app = App::new(flags);
         let fetch_data_cmd = app.fetch_data();
+        let symbols_cmd = app.api.symbols();
 
         (
             app,
             Command::batch([
                 fetch_data_cmd,
+                symbols_cmd,
                 font::load(
                     include_bytes!(concat!(
                         env!("CARGO_MANIFEST_DIR"),
@@ -154,14 +242,104 @@ impl Application for App {
         match message {
             Message::Tick => {
                 self.dashboard.tick(&self.data);
-                Command::none()
+
+                let layout = self.dashboard.take_layout_change();
+                let drawings = self.dashboard.take_drawings_change();
+                if layout.is_none() && drawings.is_none() {
+                    return Command::none();
+                }
+
+                if let Some(layout) = layout {
+                    let active = self.config.workspace.clone();
+                    if let Some(workspace) = self
+                        .config
+                        .workspaces
+                        .iter_mut()
+                        .find(|w| Some(&w.name) == active.as_ref())
+                    {
+                        workspace.layout = layout.clone();
+                    }
+
+                    self.config.layout = Some(layout);
+                }
+                if let Some(drawings) = drawings {
+                    self.config.drawings = drawings;
+                }
+                self.save_config()
+            }
+            Message::KeyPressed(key, modifiers) => {
+                use keyboard::key::Named;
+
+                if let Some(palette) = &mut self.palette {
+                    match key {
+                        keyboard::Key::Named(Named::Escape) => {
+                            self.palette = None;
+                            return Command::none();
+                        }
+                        keyboard::Key::Named(Named::ArrowUp) => {
+                            palette.move_selection(&self.data.symbols, -1);
+                            return Command::none();
+                        }
+                        keyboard::Key::Named(Named::ArrowDown) => {
+                            palette.move_selection(&self.data.symbols, 1);
+                            return Command::none();
+                        }
+                        _ => (),
+                    }
+                }
+
+                match self.config.keymap.action(&key, modifiers) {
+                    Some(action) => self.run_action(action),
+                    None => Command::none(),
+                }
+            }
+            Message::Palette(msg) => {
+                let Some(palette) = &mut self.palette else {
+                    return Command::none();
+                };
+
+                match palette.update(msg, &self.data.symbols) {
+                    Some(entry) => {
+                        self.palette = None;
+                        match entry {
+                            PaletteEntry::Action(action) => self.run_action(action),
+                            PaletteEntry::Symbol(symbol) => {
+                                self.update(DashboardMessage::CurrencyPairSelected(symbol).into())
+                            }
+                        }
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::WorkspaceSelected(name) => {
+                let Some(workspace) = self.config.workspaces.iter().find(|w| w.name == name) else {
+                    return Command::none();
+                };
+
+                let layout = workspace.layout.clone();
+                self.config.layout = Some(layout.clone());
+                self.config.workspace = Some(name);
+                self.dashboard.set_layout(layout);
+                self.track_streams();
+
+                Command::batch(
+                    self.dashboard
+                        .chart_keys()
+                        .into_iter()
+                        .map(|key| self.api.klines(key))
+                        .chain([self.save_config()]),
+                )
             }
             Message::ConfigUpdated(update) => match update {
                 Ok(new_config) => {
                     let credentials_updated = self.config.credentials() != new_config.credentials();
 
-                    self.config = new_config;
+                    self.config = *new_config;
                     self.toggle_settings();
+                    self.ws.set_reconnect(self.config.reconnect);
+                    self.data
+                        .streams
+                        .set_stale_after(self.config.reconnect.stale_after());
 
                     if credentials_updated {
                         self.api.update_credentials(
@@ -169,9 +347,10 @@ impl Application for App {
                             self.config.api_secret_key.clone(),
                         );
                         self.ws.relogin_user(&self.config.api_key);
+                        self.history_symbols.clear();
                         self.fetch_data()
                     } else {
-                        Command::none()
+                        self.refresh_history()
                     }
                 }
                 Err(err) => Command::perform(async {}, move |_| {
@@ -179,25 +358,133 @@ impl Application for App {
                 }),
             },
             Message::Ws(msg) => {
-                self.ws.update(msg, &mut self.data, &mut self.dashboard);
+                let is_user = matches!(msg, WsMessage::User(_));
+                self.ws.update(msg, &mut self.data);
+
+                if is_user {
+                    self.refresh_history()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::OrdersRecieved {
+                symbols,
+                orders,
+                failed,
+                requested_at,
+            } => {
+                for (symbol, error) in failed {
+                    self.data.notifications.push(
+                        Severity::Warning,
+                        "orders",
+                        format!("{symbol} history: {error}"),
+                    );
+                }
+                self.data.reconcile_history(orders, &symbols, requested_at);
                 Command::none()
             }
-            Message::OrdersRecieved(orders) => {
-                self.data.orders = orders;
+            Message::OpenOrdersRecieved {
+                orders,
+                requested_at,
+            } => {
+                self.data.reconcile_open_orders(orders, requested_at);
+                self.refresh_history()
+            }
+            Message::Reconcile => {
+                if !self.config.complete() {
+                    return Command::none();
+                }
+
+                let history = if self.history_symbols.is_empty() {
+                    Command::none()
+                } else {
+                    self.api
+                        .orders_history(self.history_symbols.iter().cloned().collect())
+                };
+                Command::batch([self.api.open_orders(), history])
+            }
+            Message::OrdersCanceled(canceled) => {
+                self.data.open_orders.retain(|o| {
+                    !canceled
+                        .iter()
+                        .any(|(symbol, order_id)| *symbol == o.symbol && *order_id == o.order_id)
+                });
                 Command::none()
             }
             Message::BalancesRecieved(bals) => {
                 self.data.balances = bals;
+                self.refresh_history()
+            }
+            Message::OrderPlaced(orders) => {
+                for order in orders {
+                    let price = if order.price > 0.0 {
+                        format!(" @ {}", order.price)
+                    } else {
+                        String::new()
+                    };
+                    let text = format!(
+                        "{} {:?} {:?} {}{price} placed",
+                        order.symbol, order.side, order.order_type, order.orig_qty
+                    );
+                    self.data
+                        .notifications
+                        .push(Severity::Info, "orders", text.clone());
+                    self.dashboard.notify(Toast::success(text));
+                    self.data.apply_order_update(order);
+                }
+
+                Command::none()
+            }
+            Message::OrderReplaced { old_id, order } => {
+                let text = format!("{} order moved to {}", order.symbol, order.price);
+                self.data
+                    .notifications
+                    .push(Severity::Info, "orders", text.clone());
+                self.dashboard.notify(Toast::success(text));
+                self.data
+                    .open_orders
+                    .retain(|o| o.symbol != order.symbol || o.order_id != old_id);
+                self.data.apply_order_update(order);
+
+                Command::none()
+            }
+            Message::ReplacementRejected {
+                symbol,
+                old_id,
+                error,
+            } => {
+                let text = format!("{symbol} order {old_id} canceled, new price rejected: {error}");
+                tracing::warn!("{text}");
+                self.data
+                    .notifications
+                    .push(Severity::Warning, "orders", text.clone());
+                self.dashboard.notify(Toast::error(text));
+                self.data
+                    .open_orders
+                    .retain(|o| o.symbol != symbol || o.order_id != old_id);
+
                 Command::none()
             }
-            Message::MarketChanged(new_market) => {
-                self.data.quote = new_market;
+            Message::OrderRejected { symbol, error } => {
+                let text = format!("{symbol} order rejected: {error}");
+                tracing::warn!("{text}");
+                self.data
+                    .notifications
+                    .push(Severity::Warning, "orders", text.clone());
+                self.dashboard.notify(Toast::error(text));
+
                 Command::none()
             }
             Message::DispatchErr((source, message)) => {
                 tracing::error!("error: {source}: {message}");
-                // FIXME: error panel cannot be closed and covers settings button
-                // self.errors.push(message);
+                self.data
+                    .notifications
+                    .push(Severity::Error, source, message);
+
+                Command::none()
+            }
+            Message::NotificationDismissed(id) => {
+                self.data.notifications.dismiss(id);
 
                 Command::none()
             }
@@ -207,38 +494,68 @@ impl Application for App {
                 Command::none()
             }
             Message::Dashboard(msg) => {
-                self.dashboard
-                    .update(msg, &self.api, &mut self.data, &self.ws, &self.config)
+                let command = self
+                    .dashboard
+                    .update(msg, &self.api, &mut self.data, &self.config);
+                self.track_streams();
+
+                command
             }
             Message::Settings(msg) => self.settings.update(msg),
+            Message::CloseRequested => self.ws.shutdown(),
+            Message::Exit => window::close(window::Id::MAIN),
             Message::NoOp => Command::none(),
-            Message::KlinesRecieved(kr) => match kr {
-                KlineSummaries::AllKlineSummaries(klines) => {
-                    let closes = klines.iter().map(|kline| kline.close);
-                    self.data.price_chart.push_iter_overwrite(closes);
+            Message::KlinesRecieved { key, klines } => {
+                // chart may have been closed while klines loaded
+                if self.dashboard.chart_keys().contains(&key) {
+                    self.data
+                        .price_charts
+                        .entry(key)
+                        .or_default()
+                        .replace(klines);
+                }
 
-                    Command::none()
+                Command::none()
+            }
+            Message::KlinesFailed { key, error } => {
+                tracing::error!("error: klines: {error}");
+                self.data
+                    .notifications
+                    .push(Severity::Error, "klines", error);
+                // drop in-flight marker, so chart is requested again
+                self.data.price_charts.remove(&key);
+
+                Command::none()
+            }
+            Message::OlderKlinesRecieved { key, klines } => {
+                if let Some(chart) = self.data.price_charts.get_mut(&key) {
+                    chart.prepend(klines);
                 }
-            },
+
+                Command::none()
+            }
+            Message::SymbolsRecieved(symbols) => {
+                self.data.symbols.load(symbols);
+
+                self.refresh_history()
+            }
         }
     }
 
     fn subscription(&self) -> Subscription<Message> {
         Subscription::batch([
             iced::time::every(Duration::from_millis(1000)).map(|_| Message::Tick),
+            iced::time::every(RECONCILE_PERIOD).map(|_| Message::Reconcile),
             self.ws.subscription(),
-            /*
-            keyboard::on_key_press(|key_code, modifiers| {
-                if !modifiers.command() {
-                    return None;
-                }
-                handle_hotkey(key_code)
-            })
-            */
+            keyboard::on_key_press(|key, modifiers| Some(Message::KeyPressed(key, modifiers))),
+            event::listen_with(|event, _| match event {
+                Event::Window(_, window::Event::CloseRequested) => Some(Message::CloseRequested),
+                _ => None,
+            }),
         ])
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let header = container(
             row![
                 Row::with_children(
@@ -258,19 +575,40 @@ impl Application for App {
                             let svg = svg(handle)
                                 .width(Length::Fixed(16.0))
                                 .height(Length::Fixed(16.0));
-                            return row![svg, text(format!("{price_now:.2}")).size(14)]
+                            row![svg, text(format!("{price_now:.2}")).size(14)]
                                 .spacing(4)
-                                .align_items(iced::Alignment::Center);
+                                .align_items(iced::Alignment::Center)
                         })
                         .map(Element::from)
                 )
                 .spacing(12),
                 Space::new(Length::Fill, 1),
+            ]
+            .push_maybe((!self.config.workspaces.is_empty()).then(|| {
+                pick_list(
+                    self.config
+                        .workspaces
+                        .iter()
+                        .map(|w| w.name.clone())
+                        .collect::<Vec<_>>(),
+                    self.config.workspace.clone(),
+                    Message::WorkspaceSelected,
+                )
+                .placeholder("Workspace")
+                .text_size(14)
+            }))
+            .push(
+                button(text("Reset layout").size(14))
+                    .padding(8)
+                    .style(iced::theme::Button::Text)
+                    .on_press(DashboardMessage::LayoutReset.into()),
+            )
+            .push(
                 button(text("Settings").size(14))
                     .padding(8)
                     .style(iced::theme::Button::Text)
-                    .on_press(Message::SettingsToggled)
-            ]
+                    .on_press(Message::SettingsToggled),
+            )
             .align_items(iced::Alignment::Center),
         )
         .padding([0, 16])
@@ -283,41 +621,55 @@ impl Application for App {
             ..Default::default()
         });
 
-        let err_header = container(
-            row![
-                Row::with_children(self.errors.last().map(text).map(Element::from)).spacing(12),
-                Space::new(Length::Fill, 1),
-                button(text("X").size(14))
-                    .padding(8)
-                    .style(iced::theme::Button::Text)
-                    .on_press(Message::SettingsToggled)
-            ]
-            .align_items(iced::Alignment::Center),
-        )
-        .padding([0, 16])
-        .style(container::Appearance {
-            background: Some(iced::Background::Color(Color::from_rgb(0.99, 0.03, 0.03))),
-            border: iced::Border {
-                radius: 16.0.into(),
+        let notifications = &self.data.notifications;
+        let banner = notifications.active().next().map(|n| {
+            let more = match notifications.active().count() - 1 {
+                0 => String::new(),
+                n => format!(" (+{n} more)"),
+            };
+
+            container(
+                row![
+                    text(format!(
+                        "{} {}: {}{more}",
+                        n.time.format("%H:%M:%S"),
+                        n.source,
+                        n.message
+                    ))
+                    .size(14),
+                    Space::new(Length::Fill, 1),
+                    button(text("X").size(14))
+                        .padding(8)
+                        .style(iced::theme::Button::Text)
+                        .on_press(Message::NotificationDismissed(n.id))
+                ]
+                .align_items(iced::Alignment::Center),
+            )
+            .padding([0, 16])
+            .style(container::Appearance {
+                background: Some(iced::Background::Color(Color::from_rgb(0.99, 0.03, 0.03))),
+                border: iced::Border {
+                    radius: 16.0.into(),
+                    ..Default::default()
+                },
                 ..Default::default()
-            },
-            ..Default::default()
+            })
         });
 
         let message_log = scrollable(column![container(
-            column![
-                if self.errors.is_empty() {
-                    header
-                } else {
-                    err_header
-                },
-                if self.settings_opened {
+            column![header]
+                .push_maybe(banner)
+                .push_maybe(
+                    self.palette
+                        .as_ref()
+                        .map(|p| p.view(&self.config.keymap, &self.data.symbols)),
+                )
+                .push(if self.settings_opened {
                     container(self.settings.view())
                 } else {
                     container(self.dashboard.view(&self.data).map(Message::from))
-                }
-            ]
-            .spacing(8)
+                })
+                .spacing(8)
         )
         .width(Length::Fill)
         .height(1000.0)
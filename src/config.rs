@@ -1,7 +1,9 @@
-use std::{fmt::Display, fs, io};
+use std::{collections::BTreeMap, fmt::Display, fs, io};
 
 use serde::{Deserialize, Serialize};
 
+use crate::{drawings::Drawing, keymap::Keymap, views::layout::Layout, ws::Reconnect};
+
 fn default_favorites() -> Vec<String> {
     [
         "BTCUSDT", "ETHUSDT", "LINKUSDT", "UNIUSDT", "ARBUSDT", "SYNUSDT", "OPUSDT",
@@ -11,12 +13,48 @@ fn default_favorites() -> Vec<String> {
     .collect()
 }
 
+fn default_history_quotes() -> Vec<String> {
+    vec!["USDT".to_owned()]
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub(crate) api_key: String,
     pub(crate) api_secret_key: String,
     #[serde(default = "default_favorites")]
     pub(crate) watchlist_favorites: Vec<String>,
+    /// Markets whose order history is always fetched
+    ///
+    /// Others are guessed from balances and open orders, so markets whose base asset was sold out
+    /// before start need to be listed here.
+    #[serde(default)]
+    pub(crate) history_symbols: Vec<String>,
+    /// Held assets are paired with these quotes to find traded markets
+    #[serde(default = "default_history_quotes")]
+    pub(crate) history_quotes: Vec<String>,
+    /// Dashboard layout, default one is used when missing
+    #[serde(default)]
+    pub(crate) layout: Option<Layout>,
+    #[serde(default)]
+    pub(crate) workspaces: Vec<Workspace>,
+    /// Name of workspace that follows layout changes
+    #[serde(default)]
+    pub(crate) workspace: Option<String>,
+    #[serde(default)]
+    pub(crate) keymap: Keymap,
+    /// Chart drawings by symbol, shared by all timeframes
+    #[serde(default)]
+    pub(crate) drawings: BTreeMap<String, Vec<Drawing>>,
+    /// Websocket retry delays and stale stream timeout
+    #[serde(default)]
+    pub(crate) reconnect: Reconnect,
+}
+
+/// Named dashboard layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct Workspace {
+    pub(crate) name: String,
+    pub(crate) layout: Layout,
 }
 
 impl Default for Config {
@@ -25,6 +63,14 @@ impl Default for Config {
             api_key: String::new(),
             api_secret_key: String::new(),
             watchlist_favorites: default_favorites(),
+            history_symbols: Vec::new(),
+            history_quotes: default_history_quotes(),
+            layout: None,
+            workspaces: Vec::new(),
+            workspace: None,
+            keymap: Keymap::default(),
+            drawings: BTreeMap::new(),
+            reconnect: Reconnect::default(),
         }
     }
 }
@@ -35,6 +81,15 @@ pub enum LoadError {
     Format(String),
 }
 
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::IO(err) => err.fmt(f),
+            LoadError::Format(err) => err.fmt(f),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum SaveError {
     File(io::Error),
@@ -100,6 +155,34 @@ impl Config {
     }
 }
 
+impl Config {
+    /// Add workspace, replacing one with the same name
+    pub(crate) fn upsert_workspace(&mut self, workspace: Workspace) {
+        match self
+            .workspaces
+            .iter_mut()
+            .find(|w| w.name == workspace.name)
+        {
+            Some(existing) => *existing = workspace,
+            None => self.workspaces.push(workspace),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Workspace {
+    /// Write workspace to standalone JSON file
+    pub(crate) fn export(&self, path: &std::path::Path) -> Result<(), SaveError> {
+        let json = serde_json::to_string_pretty(&self).expect("workspace serializer is valid");
+        fs::write(path, json.as_bytes()).map_err(SaveError::Write)
+    }
+
+    pub(crate) fn import(path: &std::path::Path) -> Result<Self, LoadError> {
+        let contents = fs::read_to_string(path).map_err(LoadError::IO)?;
+        serde_json::from_str(&contents).map_err(|err| LoadError::Format(err.to_string()))
+    }
+}
+
 #[cfg(target_arch = "wasm32")]
 impl Config {
     fn storage() -> Option<web_sys::Storage> {
@@ -1,10 +1,14 @@
 use ahash::AHashMap;
-use std::{collections::BTreeMap, mem::MaybeUninit};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    mem::MaybeUninit,
+};
 use tracing::trace;
 
-use binance::rest_model::{Balance, Order};
-
-use crate::ws::{prices::AssetDetails, trades::TradesEvent};
+use crate::{
+    exchange::{Balance, Kline, Order, OrderSide, SymbolInfo, PRICE_DECIMALS},
+    ws::{prices::AssetDetails, trades::TradesEvent, Streams},
+};
 
 /// Stack-allocated thread-local ring buffer with static capacity.
 pub(crate) type StaticLocalRb<T, const N: usize> = ringbuf::LocalRb<T, [MaybeUninit<T>; N]>;
@@ -136,13 +140,631 @@ impl Prices {
     }
 }
 
+/// Fixed-point price with 8 decimal places
+///
+/// Unlike `f64` it is `Ord` and hashes consistently, so it can key book levels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Price(u64);
+
+impl Price {
+    const SCALE: f64 = 100_000_000.0;
+
+    pub(crate) fn from_f64(value: f64) -> Self {
+        Self((value * Self::SCALE).round() as u64)
+    }
+
+    pub(crate) fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::SCALE
+    }
+}
+
+/// Price levels of one market
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OrderBook {
+    pub(crate) sym: String,
+    bids: BTreeMap<Price, f64>,
+    asks: BTreeMap<Price, f64>,
+}
+
+impl OrderBook {
+    pub(crate) fn new(sym: String) -> Self {
+        Self {
+            sym,
+            ..Default::default()
+        }
+    }
+
+    /// Sets absolute quantity of bid level, zero quantity removes level
+    pub(crate) fn update_bid(&mut self, price: f64, quantity: f64) {
+        Self::update_level(&mut self.bids, price, quantity);
+    }
+
+    /// Sets absolute quantity of ask level, zero quantity removes level
+    pub(crate) fn update_ask(&mut self, price: f64, quantity: f64) {
+        Self::update_level(&mut self.asks, price, quantity);
+    }
+
+    fn update_level(side: &mut BTreeMap<Price, f64>, price: f64, quantity: f64) {
+        let price = Price::from_f64(price);
+        if quantity == 0.0 {
+            side.remove(&price);
+        } else {
+            side.insert(price, quantity);
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    /// Bid levels, best first
+    pub(crate) fn bids(&self) -> impl DoubleEndedIterator<Item = (f64, f64)> + '_ {
+        self.bids.iter().rev().map(|(p, q)| (p.to_f64(), *q))
+    }
+
+    /// Ask levels, best first
+    pub(crate) fn asks(&self) -> impl DoubleEndedIterator<Item = (f64, f64)> + '_ {
+        self.asks.iter().map(|(p, q)| (p.to_f64(), *q))
+    }
+
+    pub(crate) fn best_bid(&self) -> Option<(f64, f64)> {
+        self.bids().next()
+    }
+
+    pub(crate) fn best_ask(&self) -> Option<(f64, f64)> {
+        self.asks().next()
+    }
+
+    pub(crate) fn spread(&self) -> Option<f64> {
+        Some(self.best_ask()?.0 - self.best_bid()?.0)
+    }
+
+    pub(crate) fn mid(&self) -> Option<f64> {
+        Some((self.best_ask()?.0 + self.best_bid()?.0) / 2.0)
+    }
+}
+
+/// Exchange info cache, loaded once at startup
+#[derive(Default)]
+pub(crate) struct Symbols {
+    map: AHashMap<String, SymbolInfo>,
+}
+
+impl Symbols {
+    pub(crate) fn load(&mut self, symbols: Vec<SymbolInfo>) {
+        self.map = symbols.into_iter().map(|s| (s.symbol.clone(), s)).collect();
+    }
+
+    pub(crate) fn get(&self, symbol: &str) -> Option<&SymbolInfo> {
+        self.map.get(symbol)
+    }
+
+    /// Decimals shown for prices of `symbol`
+    pub(crate) fn price_decimals(&self, symbol: &str) -> usize {
+        self.get(symbol)
+            .map_or(PRICE_DECIMALS, SymbolInfo::price_decimals)
+    }
+
+    /// Markets open for trading, in no particular order
+    pub(crate) fn trading(&self) -> impl Iterator<Item = &SymbolInfo> {
+        self.map.values().filter(|info| info.trading)
+    }
+
+    /// Base and quote assets of a symbol
+    pub(crate) fn split(&self, symbol: &str) -> Option<[&str; 2]> {
+        self.get(symbol)
+            .map(|info| [info.base.as_str(), info.quote.as_str()])
+    }
+
+    /// Find market for a symbol or a base asset
+    ///
+    /// Assets resolve to trading market quoted in `preferred_quote` if there is one, then USDT,
+    /// then any other.
+    pub(crate) fn resolve(&self, name: &str, preferred_quote: &str) -> Option<&SymbolInfo> {
+        if let Some(info) = self.get(name) {
+            return Some(info);
+        }
+
+        let candidates = || {
+            self.map
+                .values()
+                .filter(move |info| info.trading && info.base == name)
+        };
+
+        candidates()
+            .find(|info| info.quote == preferred_quote)
+            .or_else(|| candidates().find(|info| info.quote == "USDT"))
+            .or_else(|| candidates().min_by(|a, b| a.symbol.cmp(&b.symbol)))
+    }
+}
+
+/// Maximum number of notifications kept in history
+const NOTIFICATIONS_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Notification {
+    pub(crate) id: u64,
+    pub(crate) severity: Severity,
+    /// Subsystem that produced notification
+    pub(crate) source: String,
+    pub(crate) message: String,
+    pub(crate) time: chrono::DateTime<chrono::Local>,
+    pub(crate) dismissed: bool,
+}
+
+/// Notification history
+///
+/// Errors stay in banner until dismissed, everything else is only logged.
+#[derive(Default)]
+pub(crate) struct Notifications {
+    history: Vec<Notification>,
+    next_id: u64,
+}
+
+impl Notifications {
+    pub(crate) fn push(&mut self, severity: Severity, source: impl Into<String>, message: String) {
+        if self.history.len() == NOTIFICATIONS_CAPACITY {
+            self.history.remove(0);
+        }
+
+        self.history.push(Notification {
+            id: self.next_id,
+            severity,
+            source: source.into(),
+            message,
+            time: chrono::Local::now(),
+            dismissed: false,
+        });
+        self.next_id += 1;
+    }
+
+    pub(crate) fn dismiss(&mut self, id: u64) {
+        if let Some(n) = self.history.iter_mut().find(|n| n.id == id) {
+            n.dismissed = true;
+        }
+    }
+
+    /// Errors not dismissed yet, newest first
+    pub(crate) fn active(&self) -> impl Iterator<Item = &Notification> {
+        self.history
+            .iter()
+            .rev()
+            .filter(|n| n.severity == Severity::Error && !n.dismissed)
+    }
+
+    /// All notifications, newest first
+    pub(crate) fn history(&self) -> impl Iterator<Item = &Notification> {
+        self.history.iter().rev()
+    }
+}
+
+/// Maximum number of candles kept per chart, including older history loaded by panning
+const CANDLES_CAPACITY: usize = 5000;
+
+/// OHLCV history of a chart, oldest first
+#[derive(Debug, Default)]
+pub(crate) struct Candles {
+    klines: VecDeque<Kline>,
+}
+
+impl Candles {
+    pub(crate) fn replace(&mut self, klines: Vec<Kline>) {
+        let skip = klines.len().saturating_sub(CANDLES_CAPACITY);
+        self.klines = klines.into_iter().skip(skip).collect();
+    }
+
+    /// Add history older than first candle, as much as capacity allows
+    pub(crate) fn prepend(&mut self, klines: Vec<Kline>) {
+        let first = self.klines.front().map_or(i64::MAX, |k| k.open_time);
+        let room = CANDLES_CAPACITY - self.klines.len();
+        let older: Vec<_> = klines.into_iter().filter(|k| k.open_time < first).collect();
+
+        for kline in older.into_iter().rev().take(room) {
+            self.klines.push_front(kline);
+        }
+    }
+
+    /// Update current candle in place or start a new one, older candles are ignored
+    pub(crate) fn upsert(&mut self, kline: Kline) {
+        match self.klines.back_mut() {
+            Some(last) if last.open_time == kline.open_time => *last = kline,
+            Some(last) if last.open_time > kline.open_time => (),
+            _ => {
+                if self.klines.len() == CANDLES_CAPACITY {
+                    self.klines.pop_front();
+                }
+                self.klines.push_back(kline);
+            }
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.klines.is_empty()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.klines.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = &Kline> {
+        self.klines.iter()
+    }
+}
+
+/// Chart data is kept per symbol and timeframe
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct ChartKey {
+    pub(crate) symbol: String,
+    pub(crate) timeframe: String,
+}
+
 #[derive(Default)]
 pub(crate) struct AppData {
     pub(crate) prices: Prices,
-    pub(crate) book: (String, BTreeMap<String, f64>, BTreeMap<String, f64>),
-    pub(crate) trades: StaticLocalRb<TradesEvent, 1000>,
+    /// Books and trades by symbol, for symbols shown in any pane
+    pub(crate) books: AHashMap<String, OrderBook>,
+    pub(crate) trades: AHashMap<String, StaticLocalRb<TradesEvent, 1000>>,
     pub(crate) balances: Vec<Balance>,
     pub(crate) orders: Vec<Order>,
-    pub(crate) quote: String,
-    pub(crate) price_chart: StaticLocalRb<f64, 500>,
+    pub(crate) open_orders: Vec<Order>,
+    pub(crate) price_charts: AHashMap<ChartKey, Candles>,
+    pub(crate) symbols: Symbols,
+    pub(crate) notifications: Notifications,
+    /// Liveness of websocket feeds
+    pub(crate) streams: Streams,
+}
+
+impl AppData {
+    /// Markets the account is likely to have traded
+    ///
+    /// Held assets paired with each of `quotes`, markets with open orders or loaded fills and
+    /// `extra` ones. Markets sold out while running stay through their fills, ones sold out
+    /// before start are only found in `extra`.
+    pub(crate) fn traded_symbols(&self, quotes: &[String], extra: &[String]) -> BTreeSet<String> {
+        let held = self.balances.iter().flat_map(|b| {
+            quotes
+                .iter()
+                .filter(move |quote| **quote != b.asset)
+                .map(move |quote| format!("{}{quote}", b.asset))
+        });
+
+        held.filter(|symbol| self.symbols.get(symbol).is_some())
+            .chain(self.open_orders.iter().map(|o| o.symbol.clone()))
+            .chain(self.orders.iter().map(|o| o.symbol.clone()))
+            .chain(extra.iter().cloned())
+            .collect()
+    }
+
+    /// Apply latest reported state of an order to open orders and fill history
+    ///
+    /// Reports older than already known state are ignored, they can arrive after newer ones when
+    /// rest responses race the user stream.
+    pub(crate) fn apply_order_update(&mut self, order: Order) {
+        let outdated = self
+            .open_orders
+            .iter()
+            .chain(&self.orders)
+            .any(|known| known.is_same(&order) && !known.is_superseded_by(&order));
+        if outdated {
+            return;
+        }
+
+        upsert_order(&mut self.open_orders, order.clone(), |o| o.status.is_open());
+        upsert_order(&mut self.orders, order, |o| o.executed_qty > 0.0);
+    }
+
+    /// Merge rest snapshot of open orders requested at `requested_at` ms
+    ///
+    /// Orders missing from it were closed without stream reporting it, unless they are newer.
+    pub(crate) fn reconcile_open_orders(&mut self, snapshot: Vec<Order>, requested_at: u64) {
+        reconcile_orders(&mut self.open_orders, snapshot, requested_at);
+
+        // snapshot may predate stream closing the order
+        let history = &self.orders;
+        self.open_orders.retain(|o| {
+            o.status.is_open() && !history.iter().any(|h| h.is_same(o) && h.status.is_final())
+        });
+    }
+
+    /// Merge rest snapshot of fill history of `symbols` requested at `requested_at` ms
+    ///
+    /// Fills of other symbols are kept, their history failed to load or was not requested.
+    pub(crate) fn reconcile_history(
+        &mut self,
+        snapshot: Vec<Order>,
+        symbols: &[String],
+        requested_at: u64,
+    ) {
+        let (mut orders, other): (Vec<_>, Vec<_>) = std::mem::take(&mut self.orders)
+            .into_iter()
+            .partition(|o| symbols.contains(&o.symbol));
+
+        reconcile_orders(&mut orders, snapshot, requested_at);
+        orders.extend(other);
+        orders.sort_by_key(|o| std::cmp::Reverse(o.time));
+        self.orders = orders;
+    }
+
+    /// Replace balance of its asset, new assets are added and ones no longer `held` dropped
+    pub(crate) fn set_balance(&mut self, balance: Balance, held: bool) {
+        let existing = self.balances.iter().position(|b| b.asset == balance.asset);
+
+        match existing {
+            Some(i) if held => self.balances[i] = balance,
+            Some(i) => {
+                self.balances.remove(i);
+            }
+            None if held => self.balances.push(balance),
+            None => (),
+        }
+    }
+
+    /// Average price of position held in `symbol`
+    ///
+    /// Replays fills of loaded order history, sells reduce position cost proportionally.
+    pub(crate) fn average_entry(&self, symbol: &str) -> Option<f64> {
+        let mut fills: Vec<_> = self
+            .orders
+            .iter()
+            .filter(|o| o.symbol == symbol && o.executed_qty > 0.0)
+            .collect();
+        fills.sort_by_key(|o| o.update_time);
+
+        let (mut qty, mut cost) = (0.0, 0.0);
+        for fill in fills {
+            match fill.side {
+                OrderSide::Buy => {
+                    qty += fill.executed_qty;
+                    cost += fill.executed_qty * fill.fill_price();
+                }
+                OrderSide::Sell if qty > 0.0 => {
+                    let sold = fill.executed_qty.min(qty);
+                    cost *= (qty - sold) / qty;
+                    qty -= sold;
+                }
+                OrderSide::Sell => (),
+            }
+        }
+
+        (qty > 0.0).then(|| cost / qty)
+    }
+}
+
+/// Put `order` in place of its earlier state, only orders passing `keep` stay listed
+fn upsert_order(orders: &mut Vec<Order>, order: Order, keep: impl Fn(&Order) -> bool) {
+    match orders.iter().position(|o| o.is_same(&order)) {
+        Some(i) if keep(&order) => orders[i] = order,
+        Some(i) => {
+            orders.remove(i);
+        }
+        None if keep(&order) => orders.insert(0, order),
+        None => (),
+    }
+}
+
+/// Take rest `snapshot` as truth, except for stream updates newer than the request
+fn reconcile_orders(orders: &mut Vec<Order>, snapshot: Vec<Order>, requested_at: u64) {
+    let local = std::mem::replace(orders, snapshot);
+
+    for order in local {
+        match orders.iter_mut().find(|o| o.is_same(&order)) {
+            Some(o) if o.is_superseded_by(&order) => *o = order,
+            Some(_) => (),
+            // created after snapshot was taken
+            None if order.time >= requested_at => orders.push(order),
+            None => (),
+        }
+    }
+
+    orders.sort_by_key(|o| std::cmp::Reverse(o.time));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{OrderStatus, OrderType, SymbolFilters};
+
+    fn info(base: &str, quote: &str) -> SymbolInfo {
+        SymbolInfo {
+            symbol: format!("{base}{quote}"),
+            base: base.to_owned(),
+            quote: quote.to_owned(),
+            trading: true,
+            filters: SymbolFilters::default(),
+        }
+    }
+
+    #[test]
+    fn symbols_split_and_resolve() {
+        let mut symbols = Symbols::default();
+        symbols.load(vec![
+            info("BTC", "USDT"),
+            info("ETH", "BTC"),
+            info("ETH", "USDT"),
+            info("1INCH", "USDT"),
+            info("USDT", "DAI"),
+        ]);
+
+        assert_eq!(symbols.split("1INCHUSDT"), Some(["1INCH", "USDT"]));
+        assert_eq!(symbols.split("USDTDAI"), Some(["USDT", "DAI"]));
+        assert_eq!(symbols.split("INVALIDMARKET"), None);
+
+        let resolved = |name, quote| symbols.resolve(name, quote).map(|i| i.symbol.as_str());
+        assert_eq!(resolved("ETHBTC", "USDT"), Some("ETHBTC"));
+        assert_eq!(resolved("ETH", "BTC"), Some("ETHBTC"));
+        assert_eq!(resolved("ETH", "EUR"), Some("ETHUSDT"));
+        assert_eq!(resolved("USDT", "EUR"), Some("USDTDAI"));
+        assert_eq!(resolved("DOGE", "USDT"), None);
+    }
+
+    #[test]
+    fn candles_update_in_place_and_roll() {
+        let kline = |open_time, close| Kline {
+            open_time,
+            close_time: open_time + 59_999,
+            open: 1.0,
+            high: close,
+            low: 1.0,
+            close,
+            volume: 1.0,
+        };
+
+        let mut candles = Candles::default();
+        candles.replace(vec![kline(0, 1.0), kline(60_000, 2.0)]);
+        candles.upsert(kline(60_000, 3.0));
+        candles.upsert(kline(0, 5.0));
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles.iter().next_back().map(|k| k.close), Some(3.0));
+
+        candles.upsert(kline(120_000, 4.0));
+        assert_eq!(candles.len(), 3);
+        assert_eq!(candles.iter().next().map(|k| k.close), Some(1.0));
+
+        candles.prepend(vec![
+            kline(-120_000, 6.0),
+            kline(-60_000, 7.0),
+            kline(0, 8.0),
+        ]);
+        assert_eq!(candles.len(), 5);
+        assert_eq!(
+            candles.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+            vec![-120_000, -60_000, 0, 60_000, 120_000]
+        );
+        assert_eq!(candles.iter().nth(2).map(|k| k.close), Some(1.0));
+    }
+
+    #[test]
+    fn average_entry_follows_fills() {
+        let fill = |side, qty: f64, price: f64, time| Order {
+            symbol: "BTCUSDT".to_owned(),
+            order_id: time,
+            client_order_id: String::new(),
+            order_list_id: None,
+            price,
+            orig_qty: qty,
+            executed_qty: qty,
+            cumulative_quote_qty: qty * price,
+            status: OrderStatus::Filled,
+            order_type: OrderType::Limit,
+            side,
+            time,
+            update_time: time,
+        };
+
+        let mut data = AppData {
+            orders: vec![
+                fill(OrderSide::Sell, 1.0, 130.0, 3),
+                fill(OrderSide::Buy, 1.0, 120.0, 2),
+                fill(OrderSide::Buy, 1.0, 100.0, 1),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(data.average_entry("BTCUSDT"), Some(110.0));
+
+        data.orders.push(fill(OrderSide::Sell, 5.0, 90.0, 4));
+        assert_eq!(data.average_entry("BTCUSDT"), None);
+    }
+
+    #[test]
+    fn orders_follow_reports_and_snapshots() {
+        let report = |status, executed_qty: f64, update_time| Order {
+            symbol: "BTCUSDT".to_owned(),
+            order_id: 7,
+            client_order_id: "web_1".to_owned(),
+            order_list_id: None,
+            price: 100.0,
+            orig_qty: 2.0,
+            executed_qty,
+            cumulative_quote_qty: executed_qty * 100.0,
+            status,
+            order_type: OrderType::Limit,
+            side: OrderSide::Buy,
+            time: 1,
+            update_time,
+        };
+
+        let mut data = AppData::default();
+        data.apply_order_update(report(OrderStatus::New, 0.0, 1));
+        assert_eq!(data.open_orders.len(), 1);
+        assert!(data.orders.is_empty());
+
+        data.apply_order_update(report(OrderStatus::PartiallyFilled, 0.5, 2));
+        data.apply_order_update(report(OrderStatus::PartiallyFilled, 1.5, 3));
+        // late placement response does not undo fills
+        data.apply_order_update(report(OrderStatus::New, 0.0, 1));
+        assert_eq!(data.open_orders[0].executed_qty, 1.5);
+        assert_eq!(data.orders.len(), 1);
+        assert_eq!(data.orders[0].cumulative_quote_qty, 150.0);
+
+        // snapshot taken before last fill keeps newer stream state
+        data.reconcile_open_orders(vec![report(OrderStatus::PartiallyFilled, 0.5, 2)], 2);
+        assert_eq!(data.open_orders[0].executed_qty, 1.5);
+
+        data.apply_order_update(report(OrderStatus::Canceled, 1.5, 4));
+        assert!(data.open_orders.is_empty());
+        assert_eq!(data.orders[0].status, OrderStatus::Canceled);
+
+        data.reconcile_open_orders(vec![report(OrderStatus::PartiallyFilled, 1.5, 3)], 3);
+        assert!(data.open_orders.is_empty());
+
+        // order closed while stream was down disappears from snapshot
+        let mut missed = report(OrderStatus::New, 0.0, 5);
+        missed.order_id = 8;
+        data.apply_order_update(missed);
+        data.reconcile_open_orders(Vec::new(), 6);
+        assert!(data.open_orders.is_empty());
+
+        // ids repeat across symbols
+        let mut btc = report(OrderStatus::New, 0.0, 7);
+        btc.order_id = 9;
+        let mut eth = btc.clone();
+        eth.symbol = "ETHUSDT".to_owned();
+        data.apply_order_update(btc.clone());
+        data.apply_order_update(eth.clone());
+        assert_eq!(data.open_orders.len(), 2);
+
+        eth.status = OrderStatus::Canceled;
+        eth.update_time = 8;
+        data.apply_order_update(eth);
+        assert_eq!(data.open_orders.len(), 1);
+        assert_eq!(data.open_orders[0].symbol, "BTCUSDT");
+
+        btc.order_id = 0;
+        let mut eth_client = btc.clone();
+        eth_client.symbol = "ETHUSDT".to_owned();
+        assert!(!btc.is_same(&eth_client));
+
+        // fills of symbols missing from history response are kept
+        data.reconcile_history(Vec::new(), &["ETHUSDT".to_owned()], 100);
+        assert_eq!(data.orders.len(), 1);
+        data.reconcile_history(Vec::new(), &["BTCUSDT".to_owned()], 100);
+        assert!(data.orders.is_empty());
+    }
+
+    #[test]
+    fn book_levels_are_ordered_numerically() {
+        let mut book = OrderBook::new("BTCUSDT".to_owned());
+        book.update_bid(99.5, 1.0);
+        book.update_bid(100.0, 2.0);
+        book.update_bid(9.0, 3.0);
+        book.update_ask(101.0, 1.0);
+        book.update_ask(1000.0, 1.0);
+        book.update_ask(100.5, 1.0);
+
+        assert_eq!(book.best_bid(), Some((100.0, 2.0)));
+        assert_eq!(book.best_ask(), Some((100.5, 1.0)));
+        assert_eq!(book.spread(), Some(0.5));
+        assert_eq!(book.mid(), Some(100.25));
+
+        book.update_bid(100.0, 0.0);
+        assert_eq!(book.best_bid(), Some((99.5, 1.0)));
+        assert_eq!(Price::from_f64(0.1 + 0.2), Price::from_f64(0.3));
+    }
 }
@@ -0,0 +1,192 @@
+//! User drawn chart annotations
+//!
+//! Points are stored as time and price instead of candle index, so drawings stay in place
+//! when chart timeframe changes.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Retracement ratios drawn by Fibonacci tool
+pub(crate) const FIB_RATIOS: [f64; 7] = [0.0, 0.236, 0.382, 0.5, 0.618, 0.786, 1.0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DrawingTool {
+    TrendLine,
+    Level,
+    Rectangle,
+    Fibonacci,
+    Text,
+}
+
+impl DrawingTool {
+    pub(crate) const ALL: [Self; 5] = [
+        Self::TrendLine,
+        Self::Level,
+        Self::Rectangle,
+        Self::Fibonacci,
+        Self::Text,
+    ];
+
+    /// Finished with single click instead of drag
+    pub(crate) fn is_single_point(self) -> bool {
+        matches!(self, Self::Level | Self::Text)
+    }
+
+    /// Drawing spanning from `start` to `end`, `text` is used by annotations
+    pub(crate) fn build(self, start: Anchor, end: Anchor, text: &str) -> Drawing {
+        match self {
+            Self::TrendLine => Drawing::TrendLine {
+                from: start,
+                to: end,
+            },
+            Self::Level => Drawing::Level { price: start.price },
+            Self::Rectangle => Drawing::Rectangle {
+                from: start,
+                to: end,
+            },
+            Self::Fibonacci => Drawing::Fibonacci {
+                from: start,
+                to: end,
+            },
+            Self::Text => Drawing::Text {
+                at: start,
+                text: text.to_owned(),
+            },
+        }
+    }
+}
+
+impl Display for DrawingTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::TrendLine => "Line",
+            Self::Level => "Level",
+            Self::Rectangle => "Box",
+            Self::Fibonacci => "Fib",
+            Self::Text => "Text",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Point on chart, time in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Anchor {
+    pub(crate) time: i64,
+    pub(crate) price: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub(crate) enum Drawing {
+    TrendLine {
+        from: Anchor,
+        to: Anchor,
+    },
+    /// Horizontal line across whole chart
+    Level {
+        price: f64,
+    },
+    Rectangle {
+        from: Anchor,
+        to: Anchor,
+    },
+    /// Retracement levels between two prices
+    Fibonacci {
+        from: Anchor,
+        to: Anchor,
+    },
+    Text {
+        at: Anchor,
+        text: String,
+    },
+}
+
+impl Drawing {
+    /// Fibonacci levels as ratio and price, 0 is at end point of move
+    pub(crate) fn fib_levels(from: Anchor, to: Anchor) -> impl Iterator<Item = (f64, f64)> {
+        FIB_RATIOS
+            .into_iter()
+            .map(move |ratio| (ratio, to.price - (to.price - from.price) * ratio))
+    }
+
+    /// Distance in pixels from `point`, `project` maps anchors to pixels
+    pub(crate) fn distance(
+        &self,
+        point: (f32, f32),
+        project: impl Fn(Anchor) -> Option<(f32, f32)>,
+    ) -> Option<f32> {
+        match self {
+            Self::TrendLine { from, to } => {
+                Some(segment_distance(point, project(*from)?, project(*to)?))
+            }
+            Self::Level { price } => {
+                let (_, y) = project(Anchor {
+                    time: 0,
+                    price: *price,
+                })?;
+                Some((point.1 - y).abs())
+            }
+            Self::Rectangle { from, to } | Self::Fibonacci { from, to } => {
+                let (a, b) = (project(*from)?, project(*to)?);
+                let (left, right) = (a.0.min(b.0), a.0.max(b.0));
+                let (top, bottom) = (a.1.min(b.1), a.1.max(b.1));
+                let dx = (left - point.0).max(point.0 - right).max(0.0);
+                let dy = (top - point.1).max(point.1 - bottom).max(0.0);
+                Some(dx.hypot(dy))
+            }
+            Self::Text { at, .. } => {
+                let (x, y) = project(*at)?;
+                Some((point.0 - x).hypot(point.1 - y))
+            }
+        }
+    }
+}
+
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    };
+
+    (p.0 - (a.0 + t * dx)).hypot(p.1 - (a.1 + t * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fib_levels_and_hit_distance() {
+        let (from, to) = (
+            Anchor {
+                time: 0,
+                price: 100.0,
+            },
+            Anchor {
+                time: 10,
+                price: 200.0,
+            },
+        );
+
+        let levels: Vec<_> = Drawing::fib_levels(from, to).collect();
+        assert_eq!(levels.first(), Some(&(0.0, 200.0)));
+        assert_eq!(levels[3], (0.5, 150.0));
+        assert_eq!(levels.last(), Some(&(1.0, 100.0)));
+
+        // one pixel per time unit and price unit
+        let project = |a: Anchor| Some((a.time as f32, a.price as f32));
+        let line = Drawing::TrendLine { from, to };
+        assert_eq!(line.distance((0.0, 90.0), project), Some(10.0));
+        assert_eq!(line.distance((20.0, 200.0), project), Some(10.0));
+
+        let rect = Drawing::Rectangle { from, to };
+        assert_eq!(rect.distance((5.0, 150.0), project), Some(0.0));
+        assert_eq!(rect.distance((13.0, 204.0), project), Some(5.0));
+    }
+}
@@ -0,0 +1,521 @@
+//! Binance spot backend
+
+use std::sync::Arc;
+
+use binance::{
+    account::{Account, OrderCancellation, OrdersQuery},
+    api::Binance,
+    general::General,
+    market::Market,
+    rest_model::{self, Filters, KlineSummaries},
+    ws_model,
+};
+use iced_futures::futures::{
+    future::{join_all, BoxFuture},
+    FutureExt,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Balance, Exchange, ExchangeError, ExchangeResult, Kline, OcoRequest, Order, OrderRequest,
+    OrderSide, OrderStatus, OrderType, SymbolFilters, SymbolInfo, TimeInForce,
+};
+
+/// Spot OCO endpoint, not covered by binance crate
+const API_V3_ORDER_OCO: &str = "/api/v3/order/oco";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OcoOrderRequest {
+    symbol: String,
+    side: rest_model::OrderSide,
+    quantity: f64,
+    price: f64,
+    stop_price: f64,
+    stop_limit_price: f64,
+    stop_limit_time_in_force: rest_model::TimeInForce,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OcoResponse {
+    order_list_id: u64,
+    order_reports: Vec<rest_model::OCOOrderReport>,
+}
+
+pub(crate) struct BinanceSpot {
+    account: Arc<Account>,
+    market: Arc<Market>,
+    general: Arc<General>,
+}
+
+impl BinanceSpot {
+    pub(crate) fn new(public: String, secret: String) -> Self {
+        Self {
+            account: Arc::new(Binance::new(Some(public.clone()), Some(secret.clone()))),
+            market: Arc::new(Binance::new(Some(public.clone()), Some(secret.clone()))),
+            general: Arc::new(Binance::new(Some(public), Some(secret))),
+        }
+    }
+}
+
+impl Exchange for BinanceSpot {
+    fn orders_history(
+        &self,
+        symbols: Vec<String>,
+        since: u64,
+    ) -> BoxFuture<'static, Vec<(String, ExchangeResult<Vec<Order>>)>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            join_all(symbols.into_iter().map(|symbol| {
+                let account = Arc::clone(&account);
+
+                async move {
+                    let orders = account
+                        .get_all_orders(OrdersQuery {
+                            symbol: symbol.clone(),
+                            order_id: None,
+                            start_time: Some(since),
+                            end_time: None,
+                            limit: None,
+                            recv_window: None,
+                        })
+                        .await
+                        .map(|orders| {
+                            let mut orders: Vec<_> = orders.into_iter().map(Order::from).collect();
+                            orders.sort_by_key(|o| std::cmp::Reverse(o.time));
+                            orders
+                        })
+                        .map_err(ExchangeError::from);
+
+                    (symbol, orders)
+                }
+            }))
+            .await
+        }
+        .boxed()
+    }
+
+    fn balances(&self) -> BoxFuture<'static, ExchangeResult<Vec<Balance>>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            let info = account.get_account().await?;
+
+            Ok(info
+                .balances
+                .into_iter()
+                .filter(|b| b.free + b.locked > 0.0)
+                .map(Balance::from)
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn klines(
+        &self,
+        symbol: String,
+        timeframe: String,
+        limit: u16,
+        end_time: Option<u64>,
+    ) -> BoxFuture<'static, ExchangeResult<Vec<Kline>>> {
+        let market = Arc::clone(&self.market);
+
+        async move {
+            let KlineSummaries::AllKlineSummaries(klines) = market
+                .get_klines(symbol, timeframe, limit, None, end_time)
+                .await?;
+
+            Ok(klines.into_iter().map(Kline::from).collect())
+        }
+        .boxed()
+    }
+
+    fn place_order(&self, request: OrderRequest) -> BoxFuture<'static, ExchangeResult<Order>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            let transaction = account
+                .place_order(binance::account::OrderRequest {
+                    symbol: request.symbol,
+                    side: request.side.into(),
+                    order_type: request.order_type.into(),
+                    time_in_force: request.time_in_force.map(Into::into),
+                    quantity: Some(request.qty),
+                    quote_order_qty: None,
+                    price: request.price,
+                    new_client_order_id: None,
+                    stop_price: request.stop_price,
+                    iceberg_qty: None,
+                    new_order_resp_type: None,
+                    recv_window: None,
+                })
+                .await?;
+
+            Ok(transaction.into())
+        }
+        .boxed()
+    }
+
+    fn place_oco(&self, request: OcoRequest) -> BoxFuture<'static, ExchangeResult<Vec<Order>>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            let response: OcoResponse = account
+                .client
+                .post_signed_p(
+                    API_V3_ORDER_OCO,
+                    OcoOrderRequest {
+                        symbol: request.symbol,
+                        side: request.side.into(),
+                        quantity: request.qty,
+                        price: request.price,
+                        stop_price: request.stop_price,
+                        stop_limit_price: request.stop_limit_price,
+                        stop_limit_time_in_force: request.stop_limit_time_in_force.into(),
+                    },
+                    account.recv_window,
+                )
+                .await?;
+
+            Ok(response
+                .order_reports
+                .into_iter()
+                .map(|report| Order {
+                    order_list_id: Some(response.order_list_id),
+                    ..report.into()
+                })
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn open_orders(&self) -> BoxFuture<'static, ExchangeResult<Vec<Order>>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            let orders = account.get_all_open_orders().await?;
+
+            Ok(orders.into_iter().map(Order::from).collect())
+        }
+        .boxed()
+    }
+
+    fn cancel_order(
+        &self,
+        symbol: String,
+        order_id: u64,
+    ) -> BoxFuture<'static, ExchangeResult<u64>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            let canceled = account
+                .cancel_order(OrderCancellation {
+                    symbol,
+                    order_id: Some(order_id),
+                    orig_client_order_id: None,
+                    new_client_order_id: None,
+                    recv_window: None,
+                })
+                .await?;
+
+            Ok(canceled.order_id)
+        }
+        .boxed()
+    }
+
+    fn cancel_all_orders(&self, symbol: String) -> BoxFuture<'static, ExchangeResult<Vec<u64>>> {
+        let account = Arc::clone(&self.account);
+
+        async move {
+            let canceled = account.cancel_all_open_orders(symbol).await?;
+
+            Ok(canceled.into_iter().map(|o| o.order_id).collect())
+        }
+        .boxed()
+    }
+
+    fn symbols(&self) -> BoxFuture<'static, ExchangeResult<Vec<SymbolInfo>>> {
+        let general = Arc::clone(&self.general);
+
+        async move {
+            let info = general.exchange_info().await?;
+
+            Ok(info.symbols.into_iter().map(SymbolInfo::from).collect())
+        }
+        .boxed()
+    }
+}
+
+impl From<binance::errors::Error> for ExchangeError {
+    fn from(value: binance::errors::Error) -> Self {
+        match value {
+            binance::errors::Error::BinanceError { response } => Self {
+                code: Some(response.code),
+                message: response.msg,
+            },
+            other => Self {
+                code: None,
+                message: other.to_string(),
+            },
+        }
+    }
+}
+
+impl From<rest_model::OrderSide> for OrderSide {
+    fn from(value: rest_model::OrderSide) -> Self {
+        match value {
+            rest_model::OrderSide::Buy => Self::Buy,
+            rest_model::OrderSide::Sell => Self::Sell,
+        }
+    }
+}
+
+impl From<OrderSide> for rest_model::OrderSide {
+    fn from(value: OrderSide) -> Self {
+        match value {
+            OrderSide::Buy => Self::Buy,
+            OrderSide::Sell => Self::Sell,
+        }
+    }
+}
+
+impl From<rest_model::OrderType> for OrderType {
+    fn from(value: rest_model::OrderType) -> Self {
+        match value {
+            rest_model::OrderType::Limit => Self::Limit,
+            rest_model::OrderType::Market => Self::Market,
+            rest_model::OrderType::StopLoss => Self::StopLoss,
+            rest_model::OrderType::StopLossLimit => Self::StopLossLimit,
+            rest_model::OrderType::TakeProfit => Self::TakeProfit,
+            rest_model::OrderType::TakeProfitLimit => Self::TakeProfitLimit,
+            rest_model::OrderType::LimitMaker => Self::LimitMaker,
+            rest_model::OrderType::Other => Self::Other,
+        }
+    }
+}
+
+impl From<OrderType> for rest_model::OrderType {
+    fn from(value: OrderType) -> Self {
+        match value {
+            OrderType::Limit => Self::Limit,
+            OrderType::Market => Self::Market,
+            OrderType::StopLoss => Self::StopLoss,
+            OrderType::StopLossLimit => Self::StopLossLimit,
+            OrderType::TakeProfit => Self::TakeProfit,
+            OrderType::TakeProfitLimit => Self::TakeProfitLimit,
+            OrderType::LimitMaker => Self::LimitMaker,
+            OrderType::Other => Self::Other,
+        }
+    }
+}
+
+impl From<rest_model::TimeInForce> for TimeInForce {
+    fn from(value: rest_model::TimeInForce) -> Self {
+        match value {
+            rest_model::TimeInForce::GTC => Self::Gtc,
+            rest_model::TimeInForce::IOC => Self::Ioc,
+            rest_model::TimeInForce::FOK => Self::Fok,
+            rest_model::TimeInForce::GTX | rest_model::TimeInForce::Other => Self::Other,
+        }
+    }
+}
+
+impl From<TimeInForce> for rest_model::TimeInForce {
+    fn from(value: TimeInForce) -> Self {
+        match value {
+            TimeInForce::Gtc => Self::GTC,
+            TimeInForce::Ioc => Self::IOC,
+            TimeInForce::Fok => Self::FOK,
+            TimeInForce::Other => Self::Other,
+        }
+    }
+}
+
+impl From<rest_model::OrderStatus> for OrderStatus {
+    fn from(value: rest_model::OrderStatus) -> Self {
+        match value {
+            rest_model::OrderStatus::New => Self::New,
+            // `Trade` is only an execution type, order itself is partially filled at that point
+            rest_model::OrderStatus::PartiallyFilled | rest_model::OrderStatus::Trade => {
+                Self::PartiallyFilled
+            }
+            rest_model::OrderStatus::Filled => Self::Filled,
+            rest_model::OrderStatus::Canceled => Self::Canceled,
+            rest_model::OrderStatus::PendingCancel => Self::PendingCancel,
+            rest_model::OrderStatus::Rejected => Self::Rejected,
+            rest_model::OrderStatus::Expired => Self::Expired,
+        }
+    }
+}
+
+impl From<rest_model::Order> for Order {
+    fn from(value: rest_model::Order) -> Self {
+        Self {
+            symbol: value.symbol,
+            order_id: value.order_id,
+            client_order_id: value.client_order_id,
+            // -1 when order is not in a list
+            order_list_id: u64::try_from(value.order_list_id).ok(),
+            price: value.price,
+            orig_qty: value.orig_qty,
+            executed_qty: value.executed_qty,
+            cumulative_quote_qty: value.cummulative_quote_qty,
+            status: value.status.into(),
+            order_type: value.order_type.into(),
+            side: value.side.into(),
+            time: value.time,
+            update_time: value.update_time,
+        }
+    }
+}
+
+impl From<rest_model::Transaction> for Order {
+    fn from(value: rest_model::Transaction) -> Self {
+        Self {
+            symbol: value.symbol,
+            order_id: value.order_id,
+            client_order_id: value.client_order_id,
+            order_list_id: None,
+            price: value.price,
+            orig_qty: value.orig_qty,
+            executed_qty: value.executed_qty,
+            cumulative_quote_qty: value.cummulative_quote_qty,
+            status: value.status.into(),
+            order_type: value.order_type.into(),
+            side: value.side.into(),
+            time: value.transact_time,
+            update_time: value.transact_time,
+        }
+    }
+}
+
+impl From<rest_model::OCOOrderReport> for Order {
+    fn from(value: rest_model::OCOOrderReport) -> Self {
+        Self {
+            symbol: value.symbol,
+            order_id: value.order_id,
+            client_order_id: value.client_order_id.unwrap_or_default(),
+            // report does not carry it, set from list response
+            order_list_id: None,
+            price: value.price,
+            orig_qty: value.orig_qty,
+            executed_qty: value.executed_qty,
+            cumulative_quote_qty: value.cummulative_quote_qty,
+            status: value.status.into(),
+            order_type: value.order_type.into(),
+            side: value.side.into(),
+            time: value.transact_time as u64,
+            update_time: value.transact_time as u64,
+        }
+    }
+}
+
+impl From<ws_model::OrderUpdate> for Order {
+    fn from(value: ws_model::OrderUpdate) -> Self {
+        Self {
+            symbol: value.symbol,
+            order_id: value.order_id,
+            client_order_id: value
+                .origin_client_id
+                .filter(|id| !id.is_empty())
+                .or(value.client_order_id)
+                .unwrap_or_default(),
+            order_list_id: u64::try_from(value.order_list_id).ok(),
+            price: value.price,
+            orig_qty: value.qty,
+            executed_qty: value.cumulative_filled_qty,
+            cumulative_quote_qty: value.cumulative_quote_asset_transacted_qty,
+            status: value.current_order_status.into(),
+            order_type: value.order_type.into(),
+            side: value.side.into(),
+            time: value.order_creation_time,
+            update_time: value.trade_order_time,
+        }
+    }
+}
+
+impl From<rest_model::Balance> for Balance {
+    fn from(value: rest_model::Balance) -> Self {
+        Self {
+            asset: value.asset,
+            free: value.free,
+        }
+    }
+}
+
+impl From<ws_model::EventBalance> for Balance {
+    fn from(value: ws_model::EventBalance) -> Self {
+        Self {
+            asset: value.asset,
+            free: value.free,
+        }
+    }
+}
+
+impl From<rest_model::KlineSummary> for Kline {
+    fn from(value: rest_model::KlineSummary) -> Self {
+        Self {
+            open_time: value.open_time,
+            close_time: value.close_time,
+            open: value.open,
+            high: value.high,
+            low: value.low,
+            close: value.close,
+            volume: value.volume,
+        }
+    }
+}
+
+impl From<ws_model::Kline> for Kline {
+    fn from(value: ws_model::Kline) -> Self {
+        Self {
+            open_time: value.start_time,
+            close_time: value.end_time,
+            open: value.open,
+            high: value.high,
+            low: value.low,
+            close: value.close,
+            volume: value.volume,
+        }
+    }
+}
+
+impl From<rest_model::Symbol> for SymbolInfo {
+    fn from(value: rest_model::Symbol) -> Self {
+        let mut filters = SymbolFilters::default();
+        for filter in value.filters {
+            match filter {
+                Filters::PriceFilter {
+                    min_price,
+                    max_price,
+                    tick_size,
+                } => {
+                    filters.min_price = min_price;
+                    filters.max_price = max_price;
+                    filters.tick_size = tick_size;
+                }
+                Filters::LotSize {
+                    min_qty,
+                    max_qty,
+                    step_size,
+                } => {
+                    filters.min_qty = min_qty;
+                    filters.max_qty = max_qty;
+                    filters.step_size = step_size;
+                }
+                Filters::MinNotional { min_notional, .. } => filters.min_notional = min_notional,
+                _ => {}
+            }
+        }
+
+        Self {
+            symbol: value.symbol,
+            base: value.base_asset,
+            quote: value.quote_asset,
+            trading: value.status == "TRADING",
+            filters,
+        }
+    }
+}
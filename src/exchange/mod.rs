@@ -0,0 +1,445 @@
+//! Exchange-agnostic trading interface
+//!
+//! Views and app state only deal with types from this module. Each supported exchange implements
+//! [`Exchange`] and converts its own wire types into these.
+
+use std::fmt::Display;
+
+use iced_futures::futures::future::BoxFuture;
+
+mod binance;
+
+pub(crate) use self::binance::BinanceSpot;
+
+pub(crate) type ExchangeResult<T> = Result<T, ExchangeError>;
+
+/// Error returned by exchange backend
+#[derive(Debug, Clone)]
+pub(crate) struct ExchangeError {
+    /// Exchange specific error code, if exchange provided one
+    pub(crate) code: Option<i32>,
+    pub(crate) message: String,
+}
+
+impl Display for ExchangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{code}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OrderSide {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OrderType {
+    Market,
+    Limit,
+    LimitMaker,
+    StopLoss,
+    StopLossLimit,
+    TakeProfit,
+    TakeProfitLimit,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimeInForce {
+    /// Good till canceled
+    Gtc,
+    /// Immediate or cancel
+    Ioc,
+    /// Fill or kill
+    Fok,
+    Other,
+}
+
+impl Display for TimeInForce {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TimeInForce::Gtc => "GTC",
+            TimeInForce::Ioc => "IOC",
+            TimeInForce::Fok => "FOK",
+            TimeInForce::Other => "Other",
+        };
+
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OrderStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    PendingCancel,
+    Rejected,
+    Expired,
+}
+
+impl OrderStatus {
+    /// Whether order is still resting on the book
+    pub(crate) fn is_open(self) -> bool {
+        matches!(
+            self,
+            Self::New | Self::PartiallyFilled | Self::PendingCancel
+        )
+    }
+
+    /// Order will not change anymore
+    pub(crate) fn is_final(self) -> bool {
+        matches!(
+            self,
+            Self::Filled | Self::Canceled | Self::Rejected | Self::Expired
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Order {
+    pub(crate) symbol: String,
+    pub(crate) order_id: u64,
+    /// Id given by client, empty when exchange did not report it
+    pub(crate) client_order_id: String,
+    /// Order list, like OCO, the order is a leg of
+    pub(crate) order_list_id: Option<u64>,
+    pub(crate) price: f64,
+    pub(crate) orig_qty: f64,
+    pub(crate) executed_qty: f64,
+    pub(crate) cumulative_quote_qty: f64,
+    pub(crate) status: OrderStatus,
+    pub(crate) order_type: OrderType,
+    pub(crate) side: OrderSide,
+    /// Creation time in milliseconds
+    pub(crate) time: u64,
+    /// Last update time in milliseconds
+    pub(crate) update_time: u64,
+}
+
+impl Order {
+    /// Average fill price, limit price while nothing is filled
+    pub(crate) fn fill_price(&self) -> f64 {
+        if self.executed_qty > 0.0 && self.cumulative_quote_qty > 0.0 {
+            self.cumulative_quote_qty / self.executed_qty
+        } else {
+            self.price
+        }
+    }
+
+    /// Same order, by exchange id or by client id while exchange id is not known
+    ///
+    /// Both ids are only unique within one symbol.
+    pub(crate) fn is_same(&self, other: &Order) -> bool {
+        if self.symbol != other.symbol {
+            false
+        } else if self.order_id == 0 || other.order_id == 0 {
+            !self.client_order_id.is_empty() && self.client_order_id == other.client_order_id
+        } else {
+            self.order_id == other.order_id
+        }
+    }
+
+    /// Whether `update` of the same order is at least as recent as this state
+    ///
+    /// Reports carry cumulative quantities, so fills never go back and final orders stay final.
+    pub(crate) fn is_superseded_by(&self, update: &Order) -> bool {
+        if self.status.is_final() && !update.status.is_final() {
+            return false;
+        }
+
+        update.executed_qty >= self.executed_qty && update.update_time >= self.update_time
+    }
+
+    /// Limit orders can be moved to another price by cancel and replace
+    ///
+    /// Legs of order lists are not, canceling one cancels the whole list.
+    pub(crate) fn is_movable(&self) -> bool {
+        self.status.is_open()
+            && self.order_list_id.is_none()
+            && matches!(self.order_type, OrderType::Limit | OrderType::LimitMaker)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Balance {
+    pub(crate) asset: String,
+    pub(crate) free: f64,
+}
+
+/// Candle of one timeframe interval, times are in ms
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Kline {
+    pub(crate) open_time: i64,
+    pub(crate) close_time: i64,
+    pub(crate) open: f64,
+    pub(crate) high: f64,
+    pub(crate) low: f64,
+    pub(crate) close: f64,
+    pub(crate) volume: f64,
+}
+
+/// Trading rules of a market
+///
+/// Zero value means rule is not enforced by exchange.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SymbolFilters {
+    pub(crate) min_price: f64,
+    pub(crate) max_price: f64,
+    pub(crate) tick_size: f64,
+    pub(crate) min_qty: f64,
+    pub(crate) max_qty: f64,
+    pub(crate) step_size: f64,
+    pub(crate) min_notional: f64,
+}
+
+/// Market metadata
+#[derive(Debug, Clone)]
+pub(crate) struct SymbolInfo {
+    pub(crate) symbol: String,
+    pub(crate) base: String,
+    pub(crate) quote: String,
+    /// Whether market currently accepts orders
+    pub(crate) trading: bool,
+    pub(crate) filters: SymbolFilters,
+}
+
+/// Decimals shown for prices of market without known tick size
+pub(crate) const PRICE_DECIMALS: usize = 2;
+
+/// Number of decimals of positive `step`
+fn step_decimals(step: f64) -> i32 {
+    (-step.log10()).ceil().max(0.0) as i32
+}
+
+/// Round `value` to a multiple of `step`, keeping only as many decimals as `step` has
+fn round_to_step(value: f64, step: f64, round: fn(f64) -> f64) -> f64 {
+    if step <= 0.0 {
+        return value;
+    }
+
+    let scale = 10f64.powi(step_decimals(step));
+    let steps = round(value / step);
+
+    (steps * step * scale).round() / scale
+}
+
+fn is_multiple_of(value: f64, step: f64) -> bool {
+    step <= 0.0 || (value - round_to_step(value, step, f64::round)).abs() <= step * 1e-6
+}
+
+impl SymbolInfo {
+    /// Nearest price allowed by tick size
+    pub(crate) fn round_price(&self, price: f64) -> f64 {
+        round_to_step(price, self.filters.tick_size, f64::round)
+    }
+
+    /// Decimals of tick size, used for showing prices
+    pub(crate) fn price_decimals(&self) -> usize {
+        if self.filters.tick_size <= 0.0 {
+            return PRICE_DECIMALS;
+        }
+        step_decimals(self.filters.tick_size) as usize
+    }
+
+    /// Largest quantity not above `qty` allowed by step size
+    pub(crate) fn round_qty(&self, qty: f64) -> f64 {
+        round_to_step(qty, self.filters.step_size, f64::floor)
+    }
+
+    /// Check order against market rules
+    ///
+    /// `price` is limit price of order, or reference price for market orders. Notional is not
+    /// checked when it is unknown.
+    pub(crate) fn validate(&self, price: Option<f64>, qty: f64) -> Result<(), String> {
+        let f = &self.filters;
+        let symbol = &self.symbol;
+
+        if !self.trading {
+            return Err(format!("{symbol} is not trading"));
+        }
+
+        if let Some(price) = price.filter(|p| *p > 0.0) {
+            if f.min_price > 0.0 && price < f.min_price {
+                return Err(format!("{symbol} price {price} is below {}", f.min_price));
+            }
+            if f.max_price > 0.0 && price > f.max_price {
+                return Err(format!("{symbol} price {price} is above {}", f.max_price));
+            }
+            if !is_multiple_of(price, f.tick_size) {
+                return Err(format!(
+                    "{symbol} price {price} is not a multiple of tick size {}",
+                    f.tick_size
+                ));
+            }
+            if f.min_notional > 0.0 && price * qty < f.min_notional {
+                return Err(format!(
+                    "{symbol} order value {} {} is below {}",
+                    price * qty,
+                    self.quote,
+                    f.min_notional
+                ));
+            }
+        }
+
+        if qty <= 0.0 {
+            return Err(format!("{symbol} quantity must be positive"));
+        }
+        if f.min_qty > 0.0 && qty < f.min_qty {
+            return Err(format!("{symbol} quantity {qty} is below {}", f.min_qty));
+        }
+        if f.max_qty > 0.0 && qty > f.max_qty {
+            return Err(format!("{symbol} quantity {qty} is above {}", f.max_qty));
+        }
+        if !is_multiple_of(qty, f.step_size) {
+            return Err(format!(
+                "{symbol} quantity {qty} is not a multiple of step size {}",
+                f.step_size
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// New order parameters
+#[derive(Debug, Clone)]
+pub(crate) struct OrderRequest {
+    pub(crate) symbol: String,
+    pub(crate) side: OrderSide,
+    pub(crate) order_type: OrderType,
+    pub(crate) time_in_force: Option<TimeInForce>,
+    pub(crate) price: Option<f64>,
+    /// Trigger price of stop and take profit orders
+    pub(crate) stop_price: Option<f64>,
+    pub(crate) qty: f64,
+}
+
+/// One-cancels-the-other order parameters
+///
+/// Places limit maker order at `price` together with stop limit order triggered at `stop_price`.
+#[derive(Debug, Clone)]
+pub(crate) struct OcoRequest {
+    pub(crate) symbol: String,
+    pub(crate) side: OrderSide,
+    pub(crate) qty: f64,
+    pub(crate) price: f64,
+    pub(crate) stop_price: f64,
+    pub(crate) stop_limit_price: f64,
+    pub(crate) stop_limit_time_in_force: TimeInForce,
+}
+
+/// Exchange backend
+///
+/// Returned futures own everything they need, so they can be handed to `Command::perform`
+/// directly.
+pub(crate) trait Exchange: Send + Sync {
+    /// All orders of each symbol created after `since` (milliseconds), newest first
+    ///
+    /// Symbols are fetched separately, so one failing does not hide the rest.
+    fn orders_history(
+        &self,
+        symbols: Vec<String>,
+        since: u64,
+    ) -> BoxFuture<'static, Vec<(String, ExchangeResult<Vec<Order>>)>>;
+
+    /// Non-zero balances from account snapshot
+    fn balances(&self) -> BoxFuture<'static, ExchangeResult<Vec<Balance>>>;
+
+    /// Last `limit` klines of a symbol, only ones opened before `end_time` if it is set
+    fn klines(
+        &self,
+        symbol: String,
+        timeframe: String,
+        limit: u16,
+        end_time: Option<u64>,
+    ) -> BoxFuture<'static, ExchangeResult<Vec<Kline>>>;
+
+    /// Submit new order
+    fn place_order(&self, request: OrderRequest) -> BoxFuture<'static, ExchangeResult<Order>>;
+
+    /// Submit OCO pair of orders, resolves to both legs
+    fn place_oco(&self, request: OcoRequest) -> BoxFuture<'static, ExchangeResult<Vec<Order>>>;
+
+    /// Orders resting on the book for all symbols
+    fn open_orders(&self) -> BoxFuture<'static, ExchangeResult<Vec<Order>>>;
+
+    /// Cancel one order, resolves to id of canceled order
+    fn cancel_order(
+        &self,
+        symbol: String,
+        order_id: u64,
+    ) -> BoxFuture<'static, ExchangeResult<u64>>;
+
+    /// Cancel every open order of a symbol, resolves to ids of canceled orders
+    fn cancel_all_orders(&self, symbol: String) -> BoxFuture<'static, ExchangeResult<Vec<u64>>>;
+
+    /// Metadata of all markets
+    fn symbols(&self) -> BoxFuture<'static, ExchangeResult<Vec<SymbolInfo>>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_filters_round_and_validate() {
+        let info = SymbolInfo {
+            symbol: "BTCUSDT".to_owned(),
+            base: "BTC".to_owned(),
+            quote: "USDT".to_owned(),
+            trading: true,
+            filters: SymbolFilters {
+                min_price: 0.01,
+                max_price: 1_000_000.0,
+                tick_size: 0.01,
+                min_qty: 0.00001,
+                max_qty: 9000.0,
+                step_size: 0.00001,
+                min_notional: 5.0,
+            },
+        };
+
+        assert_eq!(info.round_price(65000.123), 65000.12);
+        assert_eq!(info.round_price(0.1 + 0.2), 0.3);
+        assert_eq!(info.round_qty(0.123456789), 0.12345);
+        assert_eq!(info.price_decimals(), 2);
+
+        assert!(info.validate(Some(65000.12), 0.12345).is_ok());
+        assert!(info.validate(Some(65000.123), 0.1).is_err());
+        assert!(info.validate(Some(65000.0), 0.123456).is_err());
+        assert!(info.validate(Some(65000.0), 0.00005).is_err());
+        assert!(info.validate(None, 0.00005).is_ok());
+    }
+
+    #[test]
+    fn order_list_legs_are_not_movable() {
+        let post_only = Order {
+            symbol: "BTCUSDT".to_owned(),
+            order_id: 1,
+            client_order_id: String::new(),
+            order_list_id: None,
+            price: 70000.0,
+            orig_qty: 0.1,
+            executed_qty: 0.0,
+            cumulative_quote_qty: 0.0,
+            status: OrderStatus::New,
+            order_type: OrderType::LimitMaker,
+            side: OrderSide::Sell,
+            time: 1,
+            update_time: 1,
+        };
+        assert!(post_only.is_movable());
+
+        let oco_leg = Order {
+            order_list_id: Some(5),
+            ..post_only
+        };
+        assert!(!oco_leg.is_movable());
+    }
+}
@@ -0,0 +1,351 @@
+//! Technical indicators computed over chart candles
+//!
+//! Every series is aligned with candles, values are `None` until indicator has enough history.
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::Candles;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum IndicatorKind {
+    Sma,
+    Ema,
+    Bollinger,
+    Vwap,
+    Rsi,
+    Macd,
+    Atr,
+}
+
+impl IndicatorKind {
+    pub(crate) const ALL: [Self; 7] = [
+        Self::Sma,
+        Self::Ema,
+        Self::Bollinger,
+        Self::Vwap,
+        Self::Rsi,
+        Self::Macd,
+        Self::Atr,
+    ];
+
+    /// Names and default values of parameters
+    pub(crate) fn params(self) -> &'static [(&'static str, f64)] {
+        match self {
+            Self::Sma | Self::Ema => &[("period", 20.0)],
+            Self::Bollinger => &[("period", 20.0), ("deviations", 2.0)],
+            Self::Vwap => &[],
+            Self::Rsi | Self::Atr => &[("period", 14.0)],
+            Self::Macd => &[("fast", 12.0), ("slow", 26.0), ("signal", 9.0)],
+        }
+    }
+
+    /// Drawn over price instead of in its own panel
+    pub(crate) fn is_overlay(self) -> bool {
+        matches!(self, Self::Sma | Self::Ema | Self::Bollinger | Self::Vwap)
+    }
+}
+
+impl Display for IndicatorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Sma => "SMA",
+            Self::Ema => "EMA",
+            Self::Bollinger => "BB",
+            Self::Vwap => "VWAP",
+            Self::Rsi => "RSI",
+            Self::Macd => "MACD",
+            Self::Atr => "ATR",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Indicator with parameters, stored per chart pane
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Indicator {
+    pub(crate) kind: IndicatorKind,
+    /// Same order as `IndicatorKind::params`, missing ones take default
+    #[serde(default)]
+    pub(crate) params: Vec<f64>,
+}
+
+/// Computed indicator lines and optional histogram
+#[derive(Debug, Default)]
+pub(crate) struct IndicatorOutput {
+    pub(crate) lines: Vec<Vec<Option<f64>>>,
+    pub(crate) histogram: Option<Vec<Option<f64>>>,
+}
+
+impl Indicator {
+    pub(crate) fn new(kind: IndicatorKind) -> Self {
+        Self {
+            kind,
+            params: kind.params().iter().map(|(_, value)| *value).collect(),
+        }
+    }
+
+    fn param(&self, i: usize) -> f64 {
+        self.params
+            .get(i)
+            .copied()
+            .unwrap_or_else(|| self.kind.params()[i].1)
+    }
+
+    fn period(&self, i: usize) -> usize {
+        (self.param(i) as usize).max(1)
+    }
+
+    pub(crate) fn compute(&self, candles: &Candles) -> IndicatorOutput {
+        let closes: Vec<f64> = candles.iter().map(|k| k.close).collect();
+
+        match self.kind {
+            IndicatorKind::Sma => IndicatorOutput {
+                lines: vec![sma(&closes, self.period(0))],
+                histogram: None,
+            },
+            IndicatorKind::Ema => IndicatorOutput {
+                lines: vec![ema(&closes, self.period(0))],
+                histogram: None,
+            },
+            IndicatorKind::Bollinger => {
+                let [mid, upper, lower] = bollinger(&closes, self.period(0), self.param(1));
+                IndicatorOutput {
+                    lines: vec![mid, upper, lower],
+                    histogram: None,
+                }
+            }
+            IndicatorKind::Vwap => IndicatorOutput {
+                lines: vec![vwap(candles)],
+                histogram: None,
+            },
+            IndicatorKind::Rsi => IndicatorOutput {
+                lines: vec![rsi(&closes, self.period(0))],
+                histogram: None,
+            },
+            IndicatorKind::Macd => {
+                let [macd, signal, histogram] =
+                    macd(&closes, self.period(0), self.period(1), self.period(2));
+                IndicatorOutput {
+                    lines: vec![macd, signal],
+                    histogram: Some(histogram),
+                }
+            }
+            IndicatorKind::Atr => IndicatorOutput {
+                lines: vec![atr(candles, self.period(0))],
+                histogram: None,
+            },
+        }
+    }
+}
+
+fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut sum = 0.0;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            sum += value;
+            if i >= period {
+                sum -= values[i - period];
+            }
+            (i + 1 >= period).then(|| sum / period as f64)
+        })
+        .collect()
+}
+
+/// Exponential average seeded with simple average of first `period` values
+fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    smoothed(values, period, 2.0 / (period as f64 + 1.0))
+}
+
+/// Wilder's moving average used by RSI and ATR
+fn rma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    smoothed(values, period, 1.0 / period as f64)
+}
+
+fn smoothed(values: &[f64], period: usize, alpha: f64) -> Vec<Option<f64>> {
+    let mut current: Option<f64> = None;
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            current = match current {
+                Some(prev) => Some(prev + alpha * (value - prev)),
+                None if i + 1 == period => {
+                    Some(values[..period].iter().sum::<f64>() / period as f64)
+                }
+                None => None,
+            };
+            current
+        })
+        .collect()
+}
+
+/// Run `f` over defined tail of series, keeping leading gaps
+fn on_defined(values: &[Option<f64>], f: impl Fn(&[f64]) -> Vec<Option<f64>>) -> Vec<Option<f64>> {
+    let start = values
+        .iter()
+        .position(Option::is_some)
+        .unwrap_or(values.len());
+    let defined: Vec<f64> = values[start..]
+        .iter()
+        .map(|v| v.unwrap_or_default())
+        .collect();
+
+    std::iter::repeat_n(None, start)
+        .chain(f(&defined))
+        .collect()
+}
+
+fn bollinger(values: &[f64], period: usize, deviations: f64) -> [Vec<Option<f64>>; 3] {
+    let mid = sma(values, period);
+    let deviation: Vec<_> = mid
+        .iter()
+        .enumerate()
+        .map(|(i, mean)| {
+            let mean = (*mean)?;
+            let window = &values[i + 1 - period..=i];
+            let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / period as f64;
+            Some(variance.sqrt() * deviations)
+        })
+        .collect();
+
+    let band = |sign: f64| {
+        mid.iter()
+            .zip(&deviation)
+            .map(|(m, d)| Some((*m)? + sign * (*d)?))
+            .collect()
+    };
+    let (upper, lower) = (band(1.0), band(-1.0));
+
+    [mid, upper, lower]
+}
+
+/// Volume weighted average of typical price, restarted every UTC day
+fn vwap(candles: &Candles) -> Vec<Option<f64>> {
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+
+    let mut session = None;
+    let (mut volume, mut weighted) = (0.0, 0.0);
+
+    candles
+        .iter()
+        .map(|k| {
+            let day = k.open_time.div_euclid(DAY);
+            if session != Some(day) {
+                session = Some(day);
+                (volume, weighted) = (0.0, 0.0);
+            }
+
+            volume += k.volume;
+            weighted += (k.high + k.low + k.close) / 3.0 * k.volume;
+            (volume > 0.0).then(|| weighted / volume)
+        })
+        .collect()
+}
+
+fn rsi(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let changes: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+    let gains = rma(
+        &changes.iter().map(|c| c.max(0.0)).collect::<Vec<_>>(),
+        period,
+    );
+    let losses = rma(
+        &changes.iter().map(|c| (-c).max(0.0)).collect::<Vec<_>>(),
+        period,
+    );
+
+    // first close has no change
+    std::iter::once(None)
+        .chain(gains.into_iter().zip(losses).map(|(gain, loss)| {
+            let (gain, loss) = (gain?, loss?);
+            Some(if loss == 0.0 {
+                100.0
+            } else {
+                100.0 - 100.0 / (1.0 + gain / loss)
+            })
+        }))
+        .take(values.len())
+        .collect()
+}
+
+fn macd(values: &[f64], fast: usize, slow: usize, signal: usize) -> [Vec<Option<f64>>; 3] {
+    let macd: Vec<_> = ema(values, fast)
+        .into_iter()
+        .zip(ema(values, slow))
+        .map(|(fast, slow)| Some(fast? - slow?))
+        .collect();
+    let signal = on_defined(&macd, |defined| ema(defined, signal));
+    let histogram = macd
+        .iter()
+        .zip(&signal)
+        .map(|(m, s)| Some((*m)? - (*s)?))
+        .collect();
+
+    [macd, signal, histogram]
+}
+
+fn atr(candles: &Candles, period: usize) -> Vec<Option<f64>> {
+    let mut prev_close: Option<f64> = None;
+    let true_ranges: Vec<f64> = candles
+        .iter()
+        .map(|k| {
+            let range = match prev_close {
+                Some(c) => (k.high - k.low)
+                    .max((k.high - c).abs())
+                    .max((k.low - c).abs()),
+                None => k.high - k.low,
+            };
+            prev_close = Some(k.close);
+            range
+        })
+        .collect();
+
+    rma(&true_ranges, period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(values: Vec<Option<f64>>) -> Vec<Option<f64>> {
+        values
+            .into_iter()
+            .map(|v| v.map(|v| (v * 100.0).round() / 100.0))
+            .collect()
+    }
+
+    #[test]
+    fn averages_and_oscillators() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+        assert_eq!(
+            sma(&values, 3),
+            vec![None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+        assert_eq!(
+            ema(&values, 3),
+            vec![None, None, Some(2.0), Some(3.0), Some(4.0)]
+        );
+        assert_eq!(
+            round(rsi(&[1.0, 2.0, 1.0, 2.0, 3.0], 2)),
+            vec![None, None, Some(50.0), Some(75.0), Some(87.5)]
+        );
+
+        let [mid, upper, lower] = bollinger(&[1.0, 3.0, 1.0, 3.0], 2, 2.0);
+        assert_eq!(mid, vec![None, Some(2.0), Some(2.0), Some(2.0)]);
+        assert_eq!(upper[3], Some(4.0));
+        assert_eq!(lower[3], Some(0.0));
+
+        let [macd, signal, histogram] = macd(&values, 2, 3, 2);
+        assert_eq!(macd.iter().filter(|v| v.is_some()).count(), 3);
+        assert_eq!(signal.iter().filter(|v| v.is_some()).count(), 2);
+        assert_eq!(histogram.len(), values.len());
+    }
+}
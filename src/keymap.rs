@@ -0,0 +1,223 @@
+//! Keyboard shortcuts
+//!
+//! Bindings are stored in config as `"ctrl+shift+v"` like strings keyed by action.
+
+use std::{collections::BTreeMap, fmt::Display};
+
+use iced::keyboard::{Key, Modifiers};
+use serde::{de::IntoDeserializer, Deserialize, Deserializer, Serialize};
+
+/// Everything that can be bound to a key or run from command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Action {
+    /// Open or close command palette
+    CommandPalette,
+    ToggleSettings,
+    FocusLeft,
+    FocusRight,
+    FocusUp,
+    FocusDown,
+    SplitHorizontal,
+    SplitVertical,
+    ClosePane,
+    /// Select next market of watchlist
+    NextSymbol,
+    PreviousSymbol,
+    /// Submit market pane form
+    QuickBuy,
+    QuickSell,
+}
+
+impl Action {
+    pub(crate) const ALL: [Self; 13] = [
+        Self::CommandPalette,
+        Self::ToggleSettings,
+        Self::FocusLeft,
+        Self::FocusRight,
+        Self::FocusUp,
+        Self::FocusDown,
+        Self::SplitHorizontal,
+        Self::SplitVertical,
+        Self::ClosePane,
+        Self::NextSymbol,
+        Self::PreviousSymbol,
+        Self::QuickBuy,
+        Self::QuickSell,
+    ];
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let label = match self {
+            Self::CommandPalette => "Toggle command palette",
+            Self::ToggleSettings => "Toggle settings",
+            Self::FocusLeft => "Focus pane on the left",
+            Self::FocusRight => "Focus pane on the right",
+            Self::FocusUp => "Focus pane above",
+            Self::FocusDown => "Focus pane below",
+            Self::SplitHorizontal => "Split focused pane horizontally",
+            Self::SplitVertical => "Split focused pane vertically",
+            Self::ClosePane => "Close focused pane",
+            Self::NextSymbol => "Next watchlist market",
+            Self::PreviousSymbol => "Previous watchlist market",
+            Self::QuickBuy => "Buy with market pane",
+            Self::QuickSell => "Sell with market pane",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Key with modifiers that have to be held
+#[derive(Debug, PartialEq, Eq)]
+struct Chord {
+    modifiers: Modifiers,
+    key: String,
+}
+
+impl Chord {
+    fn parse(binding: &str) -> Option<Self> {
+        let mut modifiers = Modifiers::empty();
+        let mut key = None;
+
+        for part in binding.split('+').map(|p| p.trim().to_lowercase()) {
+            match part.as_str() {
+                "ctrl" | "control" => modifiers |= Modifiers::CTRL,
+                "cmd" | "command" => modifiers |= Modifiers::COMMAND,
+                "shift" => modifiers |= Modifiers::SHIFT,
+                "alt" => modifiers |= Modifiers::ALT,
+                "super" | "logo" => modifiers |= Modifiers::LOGO,
+                "" => return None,
+                _ if key.is_some() => return None,
+                "left" | "right" | "up" | "down" => key = Some(format!("arrow{part}")),
+                "esc" => key = Some("escape".to_owned()),
+                _ => key = Some(part),
+            }
+        }
+
+        Some(Self {
+            modifiers,
+            key: key?,
+        })
+    }
+
+    fn from_event(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        let key = match key {
+            Key::Named(named) => format!("{named:?}").to_lowercase(),
+            Key::Character(c) => c.to_lowercase(),
+            Key::Unidentified => return None,
+        };
+
+        Some(Self { modifiers, key })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub(crate) struct Keymap(BTreeMap<Action, String>);
+
+/// Bindings from config over defaults, actions unknown to this version are skipped
+impl<'de> Deserialize<'de> for Keymap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bindings = BTreeMap::<String, String>::deserialize(deserializer)?;
+
+        let mut keymap = Self::default();
+        for (name, binding) in bindings {
+            let action: Result<Action, serde::de::value::Error> =
+                Action::deserialize(name.as_str().into_deserializer());
+            match action {
+                Ok(action) => {
+                    keymap.0.insert(action, binding);
+                }
+                Err(_) => tracing::warn!("unknown keymap action {name}"),
+            }
+        }
+
+        Ok(keymap)
+    }
+}
+
+impl Keymap {
+    /// Action bound to pressed key
+    pub(crate) fn action(&self, key: &Key, modifiers: Modifiers) -> Option<Action> {
+        let pressed = Chord::from_event(key, modifiers)?;
+
+        self.0
+            .iter()
+            .find(|(_, binding)| Chord::parse(binding).as_ref() == Some(&pressed))
+            .map(|(action, _)| *action)
+    }
+
+    pub(crate) fn binding(&self, action: Action) -> Option<&str> {
+        self.0.get(&action).map(String::as_str)
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+
+        Self(
+            [
+                (CommandPalette, "ctrl+k"),
+                (ToggleSettings, "ctrl+,"),
+                (FocusLeft, "ctrl+left"),
+                (FocusRight, "ctrl+right"),
+                (FocusUp, "ctrl+up"),
+                (FocusDown, "ctrl+down"),
+                (SplitHorizontal, "ctrl+shift+h"),
+                (SplitVertical, "ctrl+shift+v"),
+                (ClosePane, "ctrl+w"),
+                (NextSymbol, "alt+down"),
+                (PreviousSymbol, "alt+up"),
+                (QuickBuy, "ctrl+shift+b"),
+                (QuickSell, "ctrl+shift+s"),
+            ]
+            .into_iter()
+            .map(|(action, binding)| (action, binding.to_owned()))
+            .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced::keyboard::key::Named;
+
+    #[test]
+    fn default_bindings_resolve() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.action(&Key::Character("k".into()), Modifiers::CTRL),
+            Some(Action::CommandPalette)
+        );
+        assert_eq!(
+            keymap.action(
+                &Key::Character("V".into()),
+                Modifiers::CTRL | Modifiers::SHIFT
+            ),
+            Some(Action::SplitVertical)
+        );
+        assert_eq!(
+            keymap.action(&Key::Named(Named::ArrowLeft), Modifiers::CTRL),
+            Some(Action::FocusLeft)
+        );
+        assert_eq!(
+            keymap.action(&Key::Character("k".into()), Modifiers::empty()),
+            None
+        );
+        assert_eq!(Chord::parse("ctrl+"), None);
+        assert_eq!(Chord::parse("a+b"), None);
+    }
+
+    #[test]
+    fn config_merges_over_defaults() {
+        let keymap: Keymap =
+            serde_json::from_str(r#"{"close_pane":"ctrl+q","removed_action":"ctrl+x"}"#).unwrap();
+
+        assert_eq!(keymap.binding(Action::ClosePane), Some("ctrl+q"));
+        assert_eq!(keymap.binding(Action::CommandPalette), Some("ctrl+k"));
+    }
+}
@@ -2,6 +2,10 @@ mod api;
 mod app;
 mod config;
 mod data;
+mod drawings;
+mod exchange;
+mod indicators;
+mod keymap;
 mod message;
 mod svg_logos;
 mod theme;
@@ -23,7 +27,10 @@ fn main() -> iced::Result {
         .with_line_number(true)
         .init();
 
-    let config = Config::load().unwrap_or_default();
+    let config = Config::load().unwrap_or_else(|err| {
+        tracing::error!("failed to load config: {err}");
+        None
+    });
 
     App::run(Settings {
         window: iced::window::Settings {
@@ -42,6 +49,8 @@ fn main() -> iced::Result {
                 )
                 .unwrap(),
             ),
+            // user stream listen key is closed before exit
+            exit_on_close_request: false,
             ..Default::default()
         },
         default_font: Font {
@@ -1,15 +1,16 @@
 use std::fmt::Debug;
 use std::fmt::Display;
 
+use iced::keyboard::{Key, Modifiers};
+
 use crate::{
     config::Config,
-    views::{dashboard::DashboardMessage, settings::SettingsMessage},
+    data::ChartKey,
+    exchange::{Balance, ExchangeError, Kline, Order, SymbolInfo},
+    views::{dashboard::DashboardMessage, palette::PaletteMessage, settings::SettingsMessage},
     ws::WsMessage,
 };
 
-use binance::rest_model::KlineSummaries;
-use binance::rest_model::{Balance, Order};
-
 /// Converts Result Err variant into string, stores error source
 ///
 /// Part of event system. Used to handle throwaway results
@@ -66,17 +67,75 @@ pub(crate) enum Message {
     /// Manually triggered at interval
     Tick,
 
+    /// Refetch orders, so state missed by user stream is corrected
+    Reconcile,
+
     /// Error source and message
     DispatchErr((String, String)),
 
+    /// Id of notification closed in banner
+    NotificationDismissed(u64),
+
     /// Config update happened
-    ConfigUpdated(Result<Config, String>),
+    ConfigUpdated(Result<Box<Config>, String>),
 
     /// API responses
-    OrdersRecieved(Vec<Order>),
+    /// Rest snapshots, `requested_at` is local time of request in ms
+    ///
+    /// History covers only `symbols`, ones in `failed` come with their error.
+    OrdersRecieved {
+        symbols: Vec<String>,
+        orders: Vec<Order>,
+        failed: Vec<(String, String)>,
+        requested_at: u64,
+    },
+    OpenOrdersRecieved {
+        orders: Vec<Order>,
+        requested_at: u64,
+    },
+    /// Symbol and id of orders that were canceled
+    OrdersCanceled(Vec<(String, u64)>),
     BalancesRecieved(Vec<Balance>),
-    KlinesRecieved(KlineSummaries),
-    MarketChanged(String),
+    KlinesRecieved {
+        key: ChartKey,
+        klines: Vec<Kline>,
+    },
+    /// Initial klines of chart could not be loaded
+    KlinesFailed {
+        key: ChartKey,
+        error: String,
+    },
+    /// History loaded when chart is panned past oldest candle
+    OlderKlinesRecieved {
+        key: ChartKey,
+        klines: Vec<Kline>,
+    },
+    SymbolsRecieved(Vec<SymbolInfo>),
+    /// Orders accepted by exchange, OCO places two
+    OrderPlaced(Vec<Order>),
+    /// Order moved to another price, `old_id` was canceled
+    OrderReplaced {
+        old_id: u64,
+        order: Order,
+    },
+    /// Order moved to another price was canceled, but its replacement was refused
+    ReplacementRejected {
+        symbol: String,
+        old_id: u64,
+        error: ExchangeError,
+    },
+    /// Order refused by exchange
+    OrderRejected {
+        symbol: String,
+        error: ExchangeError,
+    },
+
+    /// Key press not captured by any widget
+    KeyPressed(Key, Modifiers),
+    Palette(PaletteMessage),
+
+    /// Switch dashboard to named workspace
+    WorkspaceSelected(String),
 
     /// Settings view events
     Settings(SettingsMessage),
@@ -87,6 +146,11 @@ pub(crate) enum Message {
     /// Event from one of websockets
     Ws(WsMessage),
 
+    /// Main window is being closed, cleanup happens before exit
+    CloseRequested,
+    /// Close main window once cleanup finished
+    Exit,
+
     /// Does nothing
     NoOp,
 }
@@ -106,6 +170,12 @@ impl From<WsMessage> for Message {
     }
 }
 
+impl From<PaletteMessage> for Message {
+    fn from(value: PaletteMessage) -> Self {
+        Self::Palette(value)
+    }
+}
+
 impl From<SettingsMessage> for Message {
     fn from(value: SettingsMessage) -> Self {
         Self::Settings(value)
@@ -195,9 +195,9 @@ struct State {
     cache: canvas::Cache,
 }
 
-impl<'a, Message, Theme> Widget<Message, Theme, Renderer> for Loader<Theme>
+impl<Message, Theme> Widget<Message, Theme, Renderer> for Loader<Theme>
 where
-    Message: 'a + Clone,
+    Message: Clone,
     Theme: StyleSheet,
 {
     fn tag(&self) -> tree::Tag {
@@ -438,7 +438,7 @@ mod easing {
 
         fn point(p: impl Into<Point>) -> lyon_algorithms::geom::Point<f32> {
             let p: Point = p.into();
-            lyon_algorithms::geom::point(p.x.min(1.0).max(0.0), p.y.min(1.0).max(0.0))
+            lyon_algorithms::geom::point(p.x.clamp(0.0, 1.0), p.y.clamp(0.0, 1.0))
         }
     }
 
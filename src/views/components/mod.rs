@@ -3,4 +3,5 @@ pub mod input;
 // pub mod list;
 pub mod loading;
 pub mod scrollbar;
+pub mod toast;
 pub mod unstyled_btn;
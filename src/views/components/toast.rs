@@ -0,0 +1,80 @@
+use std::time::{Duration, Instant};
+
+use iced::{
+    widget::{button, column, container, row, text, Space},
+    Alignment, Color, Element, Length,
+};
+
+/// How long toast stays on screen unless dismissed
+const TOAST_TTL: Duration = Duration::from_secs(8);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Success,
+    Error,
+}
+
+/// Short lived notification
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub kind: ToastKind,
+    pub text: String,
+    created: Instant,
+}
+
+impl Toast {
+    pub fn success(text: String) -> Self {
+        Self::new(ToastKind::Success, text)
+    }
+
+    pub fn error(text: String) -> Self {
+        Self::new(ToastKind::Error, text)
+    }
+
+    fn new(kind: ToastKind, text: String) -> Self {
+        Self {
+            kind,
+            text,
+            created: Instant::now(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.created.elapsed() > TOAST_TTL
+    }
+}
+
+/// Column of toasts, newest last, each with close button producing `on_close(index)`
+pub fn toasts<'a, M: Clone + 'a>(toasts: &'a [Toast], on_close: fn(usize) -> M) -> Element<'a, M> {
+    column(toasts.iter().enumerate().map(|(i, toast)| {
+        let background = match toast.kind {
+            ToastKind::Success => Color::from_rgb(0.08, 0.35, 0.2),
+            ToastKind::Error => Color::from_rgb(0.55, 0.08, 0.08),
+        };
+
+        container(
+            row![
+                text(&toast.text).size(14),
+                Space::new(Length::Fill, 1),
+                button(text("X").size(14))
+                    .padding(4)
+                    .style(iced::theme::Button::Text)
+                    .on_press(on_close(i))
+            ]
+            .align_items(Alignment::Center),
+        )
+        .padding([0, 12])
+        .style(move |_: &_| container::Appearance {
+            background: Some(iced::Background::Color(background)),
+            text_color: Some(Color::WHITE),
+            border: iced::Border {
+                radius: 8.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .into()
+    }))
+    .spacing(4)
+    .into()
+}
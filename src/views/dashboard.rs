@@ -1,30 +1,47 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use serde::{Deserialize, Serialize};
+
 use iced::{
     theme,
     widget::{
-        button,
-        pane_grid::{self, Configuration},
-        responsive, row, text, PaneGrid,
+        button, column, pane_grid, pick_list, responsive, row, text, text_input, tooltip, PaneGrid,
     },
     Command, Element, Font, Length,
 };
 
 use crate::{
-    api::Client, config::Config, data::AppData, message::Message, theme::h2c, ws::Websockets,
+    api::{Client, DEFAULT_TIMEFRAME},
+    config::Config,
+    data::{AppData, ChartKey},
+    drawings::{Drawing, DrawingTool},
+    indicators::{Indicator, IndicatorKind},
+    message::Message,
+    theme::h2c,
+    ws::{FeedStatus, Stream},
 };
 
+use super::components::toast::{toasts, Toast};
+use super::layout::Layout;
 use super::panes::{
     balances::BalancesPane,
     book::BookPane,
     calculator::{CalculatorPane, CalculatorPaneMessage},
     chart::ChartPane,
+    depth::{DepthPane, DEFAULT_DEPTH_RANGE},
+    log::LogPane,
     market::{Market, MarketPanelMessage},
+    open_orders::{OpenOrdersMessage, OpenOrdersPane},
     orders::OrdersPane,
     style,
     trades::TradesPane,
     watchlist::{WatchlistMessage, WatchlistPane},
 };
 
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub(crate) enum PaneType {
     Prices,
     Book,
@@ -32,38 +49,114 @@ pub(crate) enum PaneType {
     Market,
     Balances,
     Orders,
+    OpenOrders,
     Calculator,
     Chart,
+    Log,
+    Depth,
 }
 
-impl ToString for PaneType {
-    fn to_string(&self) -> String {
-        match self {
+impl PaneType {
+    pub(crate) const ALL: [Self; 11] = [
+        Self::Prices,
+        Self::Chart,
+        Self::Book,
+        Self::Depth,
+        Self::Trades,
+        Self::Market,
+        Self::Balances,
+        Self::Orders,
+        Self::OpenOrders,
+        Self::Calculator,
+        Self::Log,
+    ];
+
+    /// Whether each instance of pane can show its own symbol
+    pub(crate) fn has_symbol(self) -> bool {
+        matches!(self, Self::Chart | Self::Book | Self::Trades | Self::Depth)
+    }
+}
+
+impl Display for PaneType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
             PaneType::Prices => "Watchlist",
             PaneType::Book => "Book",
             PaneType::Trades => "Trades",
             PaneType::Market => "Market",
             PaneType::Balances => "Balances",
             PaneType::Orders => "Orders",
+            PaneType::OpenOrders => "Open Orders",
             PaneType::Calculator => "Calculator",
             PaneType::Chart => "Chart",
-        }
-        .to_string()
+            PaneType::Log => "Log",
+            PaneType::Depth => "Depth",
+        };
+
+        f.write_str(name)
     }
 }
 
 pub(crate) struct Pane {
-    id: PaneType,
-    is_pinned: bool,
+    pub(crate) id: PaneType,
+    pub(crate) is_pinned: bool,
+    /// Symbol of chart, book, trades and depth panes, `None` follows selected pair
+    pub(crate) symbol: Option<String>,
+    /// Symbol being typed in title bar
+    symbol_input: String,
+    /// Kline interval of chart panes
+    pub(crate) timeframe: String,
+    /// Indicators of chart panes
+    pub(crate) indicators: Vec<Indicator>,
+    /// Indicator parameters being typed, same shape as `indicators` params
+    pub(crate) param_inputs: Vec<Vec<String>>,
+    /// Tool used by next click on chart
+    pub(crate) drawing_tool: Option<DrawingTool>,
+    /// Text placed by annotation tool
+    pub(crate) annotation: String,
+    /// Percent of mid price shown by depth panes on each side
+    pub(crate) depth_range: f64,
 }
 
 impl Pane {
-    pub fn new(ty: PaneType) -> Self {
+    pub(crate) fn new(
+        id: PaneType,
+        is_pinned: bool,
+        symbol: Option<String>,
+        timeframe: Option<String>,
+    ) -> Self {
         Self {
-            id: ty,
-            is_pinned: false,
+            id,
+            is_pinned,
+            symbol_input: symbol.clone().unwrap_or_default(),
+            symbol,
+            timeframe: timeframe.unwrap_or_else(|| DEFAULT_TIMEFRAME.to_owned()),
+            indicators: Vec::new(),
+            param_inputs: Vec::new(),
+            drawing_tool: None,
+            annotation: String::new(),
+            depth_range: DEFAULT_DEPTH_RANGE,
         }
     }
+
+    pub(crate) fn with_indicators(mut self, indicators: Vec<Indicator>) -> Self {
+        self.indicators = indicators;
+        self.reset_param_inputs();
+        self
+    }
+
+    pub(crate) fn with_depth_range(mut self, range: Option<f64>) -> Self {
+        self.depth_range = range.unwrap_or(DEFAULT_DEPTH_RANGE);
+        self
+    }
+
+    fn reset_param_inputs(&mut self) {
+        self.param_inputs = self
+            .indicators
+            .iter()
+            .map(|i| i.params.iter().map(f64::to_string).collect())
+            .collect();
+    }
 }
 
 pub fn view_controls<'a>(
@@ -72,7 +165,15 @@ pub fn view_controls<'a>(
     is_pinned: bool,
     is_maximized: bool,
 ) -> Element<'a, DashboardMessage> {
-    let mut row = row![].spacing(5);
+    let mut row = row![
+        pick_list(PaneType::ALL.as_slice(), None::<PaneType>, move |kind| {
+            DashboardMessage::PaneAdded(pane, kind)
+        })
+        .placeholder("+ Add")
+        .text_size(12)
+        .padding([0, 4])
+    ]
+    .spacing(5);
 
     if total_panes > 1 {
         let toggle = {
@@ -121,15 +222,46 @@ pub(crate) enum DashboardMessage {
     Maximize(pane_grid::Pane),
     Restore,
     Close(pane_grid::Pane),
+    /// Split pane to open new one next to it
+    PaneAdded(pane_grid::Pane, PaneType),
+    PaneSymbolInput(pane_grid::Pane, String),
+    PaneSymbolSet(pane_grid::Pane),
+    FocusAdjacent(pane_grid::Direction),
+    /// Open copy of focused pane next to it
+    SplitFocused(pane_grid::Axis),
+    CloseFocused,
 
     Watchlist(WatchlistMessage),
     Market(MarketPanelMessage),
     Calculator(CalculatorPaneMessage),
+    OpenOrders(OpenOrdersMessage),
 
     CurrencyPairSelected(String),
+    /// Index of toast to close
+    ToastClosed(usize),
+    /// Replace current layout with default one
+    LayoutReset,
 
-    // TODO: move to chart
-    TimeframeChanged(String),
+    TimeframeChanged(pane_grid::Pane, String),
+    /// Percent of mid price shown by depth pane
+    DepthRangeChanged(pane_grid::Pane, f64),
+    /// Symbol and id of open order dragged to new price on chart
+    OrderMoved(String, u64, f64),
+    /// Chart was panned past oldest loaded candle
+    HistoryRequested(ChartKey),
+    IndicatorAdded(pane_grid::Pane, IndicatorKind),
+    /// Index of indicator in pane
+    IndicatorRemoved(pane_grid::Pane, usize),
+    /// Indicator and parameter index with typed value
+    IndicatorParamInput(pane_grid::Pane, usize, usize, String),
+    /// `None` goes back to panning
+    DrawingToolSelected(pane_grid::Pane, Option<DrawingTool>),
+    AnnotationInput(pane_grid::Pane, String),
+    /// Drawing finished on chart of pane
+    DrawingAdded(pane_grid::Pane, Drawing),
+    /// Symbol and index of drawing
+    DrawingRemoved(String, usize),
+    DrawingsCleared(String),
 }
 
 impl From<WatchlistMessage> for DashboardMessage {
@@ -144,6 +276,12 @@ impl From<CalculatorPaneMessage> for DashboardMessage {
     }
 }
 
+impl From<OpenOrdersMessage> for DashboardMessage {
+    fn from(value: OpenOrdersMessage) -> Self {
+        Self::OpenOrders(value)
+    }
+}
+
 impl From<MarketPanelMessage> for DashboardMessage {
     fn from(value: MarketPanelMessage) -> Self {
         Self::Market(value)
@@ -153,67 +291,44 @@ impl From<MarketPanelMessage> for DashboardMessage {
 pub(crate) struct DashboardView {
     focus: Option<pane_grid::Pane>,
     panes: pane_grid::State<Pane>,
+    /// Layout differs from last saved one
+    layout_changed: bool,
+    /// Chart drawings by symbol
+    drawings: BTreeMap<String, Vec<Drawing>>,
+    drawings_changed: bool,
     watchlist: WatchlistPane,
     chart: ChartPane,
+    depth: DepthPane,
     calculator: CalculatorPane,
     market: Market,
     book: BookPane,
     orders: OrdersPane,
+    open_orders: OpenOrdersPane,
+    log: LogPane,
+    toasts: Vec<Toast>,
     balances: BalancesPane,
     trades: TradesPane,
 }
 
-macro_rules! v {
-    ($r: expr, $a: expr, $b: expr) => {
-        b![Vertical, $r, $a, $b]
-    };
-}
-macro_rules! h {
-    ($r: expr, $a: expr, $b: expr) => {
-        b![Horizontal, $r, $a, $b]
-    };
-}
-macro_rules! b {
-    ($d: ident, $r: expr, $a: expr, $b: expr) => {
-        Configuration::Split {
-            axis: pane_grid::Axis::$d,
-            ratio: $r,
-            a: Box::new($a),
-            b: Box::new($b),
-        }
-    };
-}
-macro_rules! pane {
-    ($p: ident) => {
-        Configuration::Pane(Pane::new(PaneType::$p))
-    };
-}
-
 impl DashboardView {
-    pub(crate) fn new() -> Self {
-        let panes = pane_grid::State::with_configuration(h![
-            0.65,
-            v![
-                0.15,
-                h![0.6, pane![Prices], pane![Balances]],
-                v![
-                    0.5,
-                    pane![Chart],
-                    v![0.6, h![0.33, pane![Market], pane![Trades]], pane![Book]]
-                ]
-            ],
-            v![0.7, pane![Orders], pane![Calculator]]
-        ]);
-
+    /// Restore saved layout or build default one
+    pub(crate) fn new(layout: Option<Layout>, drawings: BTreeMap<String, Vec<Drawing>>) -> Self {
         Self {
             focus: None,
-            panes,
+            panes: layout.unwrap_or_default().into_state(),
+            layout_changed: false,
+            drawings,
+            drawings_changed: false,
             watchlist: WatchlistPane::new(),
             chart: ChartPane::new(),
+            depth: DepthPane::new(),
             calculator: CalculatorPane::new(),
             market: Market::new(),
             book: BookPane::new(),
             orders: OrdersPane::new(),
+            open_orders: OpenOrdersPane::new(),
+            log: LogPane::new(),
+            toasts: Vec::new(),
             balances: BalancesPane::new(),
             trades: TradesPane::new(),
         }
@@ -224,48 +339,210 @@ impl DashboardView {
         self.market.pair()
     }
 
+    fn pane_symbol<'a>(&'a self, pane: &'a Pane) -> &'a str {
+        pane.symbol.as_deref().unwrap_or(self.pair())
+    }
+
+    /// Symbols whose book and trades are shown, selected pair is always included
+    ///
+    /// Charts only need klines, see `chart_keys`.
+    pub(crate) fn symbols(&self) -> BTreeSet<String> {
+        self.panes
+            .iter()
+            .filter(|(_, pane)| pane.id.has_symbol() && pane.id != PaneType::Chart)
+            .filter_map(|(_, pane)| pane.symbol.clone())
+            .chain([self.pair().to_owned()])
+            .collect()
+    }
+
+    fn chart_key(&self, pane: &Pane) -> ChartKey {
+        ChartKey {
+            symbol: self.pane_symbol(pane).to_owned(),
+            timeframe: pane.timeframe.clone(),
+        }
+    }
+
+    /// Websocket feed whose data pane shows
+    fn pane_stream(&self, pane: &Pane) -> Option<Stream> {
+        let symbol = || self.pane_symbol(pane).to_owned();
+
+        match pane.id {
+            PaneType::Chart => Some(Stream::Klines(self.chart_key(pane))),
+            PaneType::Book | PaneType::Depth => Some(Stream::Book(symbol())),
+            PaneType::Trades => Some(Stream::Trades(symbol())),
+            PaneType::Prices => Some(Stream::Prices),
+            PaneType::Balances | PaneType::Orders | PaneType::OpenOrders => Some(Stream::User),
+            PaneType::Market | PaneType::Calculator | PaneType::Log => None,
+        }
+    }
+
+    /// Data shown in chart panes
+    pub(crate) fn chart_keys(&self) -> BTreeSet<ChartKey> {
+        self.panes
+            .iter()
+            .filter(|(_, pane)| pane.id == PaneType::Chart)
+            .map(|(_, pane)| self.chart_key(pane))
+            .collect()
+    }
+
+    /// Request klines for charts that have no data yet
+    fn load_charts(&self, api: &Client, data: &mut AppData) -> Command<Message> {
+        Command::batch(self.chart_keys().into_iter().filter_map(|key| {
+            if data.price_charts.contains_key(&key) {
+                return None;
+            }
+
+            // empty chart marks request in flight
+            data.price_charts.insert(key.clone(), Default::default());
+            Some(api.klines(key))
+        }))
+    }
+
+    /// Replace panes with another layout
+    pub(crate) fn set_layout(&mut self, layout: Layout) {
+        self.panes = layout.into_state();
+        self.focus = None;
+        self.layout_changed = false;
+    }
+
     pub(crate) fn update(
         &mut self,
         message: DashboardMessage,
         api: &Client,
         data: &mut AppData,
-        ws: &Websockets,
         config: &Config,
     ) -> Command<Message> {
-        match message {
+        let command = match message {
             DashboardMessage::Clicked(pane) => {
                 self.focus = Some(pane);
                 Command::none()
             }
             DashboardMessage::Resized(pane_grid::ResizeEvent { split, ratio }) => {
                 self.panes.resize(split, ratio);
+                self.layout_changed = true;
                 Command::none()
             }
             DashboardMessage::Dragged(pane_grid::DragEvent::Dropped { pane, target }) => {
                 self.panes.drop(pane, target);
+                self.layout_changed = true;
                 Command::none()
             }
             DashboardMessage::Dragged(_) => Command::none(),
             DashboardMessage::Maximize(pane) => {
                 self.panes.maximize(pane);
+                self.layout_changed = true;
                 Command::none()
             }
             DashboardMessage::Restore => {
                 self.panes.restore();
+                self.layout_changed = true;
                 Command::none()
             }
             DashboardMessage::Close(pane) => {
                 if let Some((_, sibling)) = self.panes.close(pane) {
                     self.focus = Some(sibling);
                 }
+                self.layout_changed = true;
+                Command::none()
+            }
+            DashboardMessage::PaneAdded(pane, kind) => {
+                let new_pane = Pane::new(kind, false, None, None);
+                if let Some((new, _)) = self.panes.split(pane_grid::Axis::Vertical, pane, new_pane)
+                {
+                    self.focus = Some(new);
+                }
+                self.layout_changed = true;
+                Command::none()
+            }
+            DashboardMessage::FocusAdjacent(direction) => {
+                self.focus = match self.focus {
+                    Some(pane) => self.panes.adjacent(pane, direction).or(Some(pane)),
+                    None => self.panes.iter().next().map(|(pane, _)| *pane),
+                };
+                Command::none()
+            }
+            DashboardMessage::SplitFocused(axis) => {
+                let Some((focus, pane)) = self.focus.and_then(|f| Some((f, self.panes.get(f)?)))
+                else {
+                    return Command::none();
+                };
+
+                let new_pane = Pane::new(
+                    pane.id,
+                    false,
+                    pane.symbol.clone(),
+                    Some(pane.timeframe.clone()),
+                )
+                .with_indicators(pane.indicators.clone())
+                .with_depth_range(Some(pane.depth_range));
+                if let Some((new, _)) = self.panes.split(axis, focus, new_pane) {
+                    self.focus = Some(new);
+                    self.layout_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::CloseFocused => {
+                let closable = self.panes.len() > 1
+                    && self
+                        .focus
+                        .and_then(|f| self.panes.get(f))
+                        .is_some_and(|pane| !pane.is_pinned);
+
+                match self.focus {
+                    Some(pane) if closable => {
+                        return self.update(DashboardMessage::Close(pane), api, data, config)
+                    }
+                    _ => Command::none(),
+                }
+            }
+            DashboardMessage::PaneSymbolInput(pane, input) => {
+                if let Some(pane) = self.panes.get_mut(pane) {
+                    pane.symbol_input = input.to_uppercase();
+                }
                 Command::none()
             }
-            DashboardMessage::CurrencyPairSelected(pair) => {
-                ws.track_new_currency_pair(&pair);
-                self.market.set_currency_pair(pair);
+            DashboardMessage::PaneSymbolSet(pane) => {
+                let Some(pane) = self.panes.get_mut(pane) else {
+                    return Command::none();
+                };
+                let input = pane.symbol_input.trim();
 
+                if input.is_empty() {
+                    pane.symbol = None;
+                    self.layout_changed = true;
+                    return Command::none();
+                }
+
+                match data.symbols.resolve(input, "USDT") {
+                    Some(info) => {
+                        pane.symbol = Some(info.symbol.clone());
+                        pane.symbol_input = info.symbol.clone();
+                        self.layout_changed = true;
+                        Command::none()
+                    }
+                    None => unknown_market(input.to_owned()),
+                }
+            }
+            DashboardMessage::LayoutReset => {
+                self.panes = Layout::default().into_state();
+                self.focus = None;
+                self.layout_changed = true;
                 Command::none()
             }
+            DashboardMessage::CurrencyPairSelected(name) => {
+                let quote = data
+                    .symbols
+                    .split(self.pair())
+                    .map_or("USDT", |[_, quote]| quote);
+
+                match data.symbols.resolve(&name, quote) {
+                    Some(info) => {
+                        self.market.set_currency_pair(info.symbol.clone());
+                        Command::none()
+                    }
+                    None => unknown_market(name),
+                }
+            }
             DashboardMessage::Watchlist(msg) => self
                 .watchlist
                 .update(msg, data, config)
@@ -276,36 +553,198 @@ impl DashboardView {
                 .update(msg)
                 .map(DashboardMessage::from)
                 .map(Message::from),
-            DashboardMessage::TimeframeChanged(tf) => api.klines(self.pair().to_owned(), tf),
-            DashboardMessage::Market(msg) => self.market.update(msg, api, data, ws),
-        }
+            DashboardMessage::TimeframeChanged(pane, timeframe) => {
+                if let Some(pane) = self.panes.get_mut(pane) {
+                    pane.timeframe = timeframe;
+                    self.layout_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::DepthRangeChanged(pane, range) => {
+                if let Some(pane) = self.panes.get_mut(pane) {
+                    pane.depth_range = range;
+                    self.layout_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::OrderMoved(symbol, order_id, price) => {
+                let Some(order) = data
+                    .open_orders
+                    .iter()
+                    .find(|o| o.symbol == symbol && o.order_id == order_id)
+                else {
+                    return Command::none();
+                };
+
+                let info = data.symbols.get(&order.symbol);
+                let price = info.map_or(price, |info| info.round_price(price));
+                let qty = order.orig_qty - order.executed_qty;
+
+                match info.map_or(Ok(()), |info| info.validate(Some(price), qty)) {
+                    Ok(()) => api.replace_order(order.clone(), price),
+                    Err(e) => dispatch_err("move order", e),
+                }
+            }
+            DashboardMessage::HistoryRequested(key) => {
+                match data.price_charts.get(&key).and_then(|c| c.iter().next()) {
+                    Some(oldest) => api.older_klines(key, oldest.open_time),
+                    None => Command::none(),
+                }
+            }
+            DashboardMessage::IndicatorAdded(pane, kind) => {
+                if let Some(pane) = self.panes.get_mut(pane) {
+                    pane.indicators.push(Indicator::new(kind));
+                    pane.reset_param_inputs();
+                    self.layout_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::IndicatorRemoved(pane, i) => {
+                if let Some(pane) = self.panes.get_mut(pane).filter(|p| i < p.indicators.len()) {
+                    pane.indicators.remove(i);
+                    pane.reset_param_inputs();
+                    self.layout_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::IndicatorParamInput(pane, i, param, input) => {
+                let Some(pane) = self.panes.get_mut(pane) else {
+                    return Command::none();
+                };
+
+                if let Ok(value) = input.parse::<f64>() {
+                    if let Some(p) = pane
+                        .indicators
+                        .get_mut(i)
+                        .and_then(|indicator| indicator.params.get_mut(param))
+                        .filter(|_| value > 0.0)
+                    {
+                        *p = value;
+                        self.layout_changed = true;
+                    }
+                }
+                if let Some(p) = pane.param_inputs.get_mut(i).and_then(|p| p.get_mut(param)) {
+                    *p = input;
+                }
+                Command::none()
+            }
+            DashboardMessage::DrawingToolSelected(pane, tool) => {
+                if let Some(pane) = self.panes.get_mut(pane) {
+                    pane.drawing_tool = tool;
+                }
+                Command::none()
+            }
+            DashboardMessage::AnnotationInput(pane, input) => {
+                if let Some(pane) = self.panes.get_mut(pane) {
+                    pane.annotation = input;
+                }
+                Command::none()
+            }
+            DashboardMessage::DrawingAdded(pane, drawing) => {
+                let pair = self.pair().to_owned();
+                let Some(pane) = self.panes.get_mut(pane) else {
+                    return Command::none();
+                };
+
+                // one drawing per tool pick, so next click pans again
+                pane.drawing_tool = None;
+                let symbol = pane.symbol.clone().unwrap_or(pair);
+                self.drawings.entry(symbol).or_default().push(drawing);
+                self.drawings_changed = true;
+                Command::none()
+            }
+            DashboardMessage::DrawingRemoved(symbol, i) => {
+                if let Some(drawings) = self.drawings.get_mut(&symbol).filter(|d| i < d.len()) {
+                    drawings.remove(i);
+                    self.drawings_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::DrawingsCleared(symbol) => {
+                if self.drawings.remove(&symbol).is_some() {
+                    self.drawings_changed = true;
+                }
+                Command::none()
+            }
+            DashboardMessage::Market(msg) => self.market.update(msg, api, data),
+            DashboardMessage::OpenOrders(msg) => self.open_orders.update(msg, api),
+            DashboardMessage::ToastClosed(i) => {
+                if i < self.toasts.len() {
+                    self.toasts.remove(i);
+                }
+                Command::none()
+            }
+        };
+
+        Command::batch([command, self.load_charts(api, data)])
     }
 
     pub(crate) fn tick(&mut self, data: &AppData) {
         self.calculator.tick(data);
+        self.toasts.retain(|t| !t.is_expired());
+    }
+
+    /// Current layout if it changed since last call
+    pub(crate) fn take_layout_change(&mut self) -> Option<Layout> {
+        std::mem::take(&mut self.layout_changed).then(|| Layout::from_state(&self.panes))
+    }
+
+    /// All drawings if they changed since last call
+    pub(crate) fn take_drawings_change(&mut self) -> Option<BTreeMap<String, Vec<Drawing>>> {
+        std::mem::take(&mut self.drawings_changed).then(|| self.drawings.clone())
+    }
+
+    /// Show short lived notification above panes
+    pub(crate) fn notify(&mut self, toast: Toast) {
+        self.toasts.push(toast);
     }
 
     pub(crate) fn view<'a>(&'a self, data: &'a AppData) -> Element<'a, DashboardMessage> {
         let focus = self.focus;
         let total_panes = self.panes.len();
 
-        PaneGrid::new(&self.panes, |id, pane, is_maximized| {
+        let panes = PaneGrid::new(&self.panes, |id, pane, is_maximized| {
             let is_focused = focus == Some(id);
 
-            let title = row![text(pane.id.to_string())].spacing(5);
+            let symbol_input = pane.id.has_symbol().then(|| {
+                text_input(self.pair(), &pane.symbol_input)
+                    .on_input(move |input| DashboardMessage::PaneSymbolInput(id, input))
+                    .on_submit(DashboardMessage::PaneSymbolSet(id))
+                    .size(12)
+                    .padding([0, 4])
+                    .width(100)
+            });
+            let feed = self
+                .pane_stream(pane)
+                .map(|stream| feed_indicator(data, &stream));
+            let title = row![text(pane.id.to_string())]
+                .push_maybe(symbol_input)
+                .push_maybe(feed)
+                .spacing(5)
+                .align_items(iced::Alignment::Center);
+            let symbol = self.pane_symbol(pane);
             let title_bar = pane_grid::TitleBar::new(title)
                 .controls(view_controls(id, total_panes, pane.is_pinned, is_maximized))
                 .padding([8, 12]);
 
-            pane_grid::Content::new(responsive(|_size| match pane.id {
+            pane_grid::Content::new(responsive(move |_size| match pane.id {
                 PaneType::Prices => self.watchlist.view(data).map(DashboardMessage::from),
-                PaneType::Chart => self.chart.view(data),
-                PaneType::Book => self.book.view(data),
-                PaneType::Trades => self.trades.view(data),
+                PaneType::Chart => self.chart.view(
+                    data,
+                    id,
+                    &self.chart_key(pane),
+                    pane,
+                    self.drawings.get(symbol).map_or(&[], Vec::as_slice),
+                ),
+                PaneType::Book => self.book.view(data, symbol),
+                PaneType::Depth => self.depth.view(data, id, symbol, pane),
+                PaneType::Trades => self.trades.view(data, symbol),
                 PaneType::Market => self.market.view().map(DashboardMessage::from),
                 PaneType::Balances => self.balances.view(data),
                 PaneType::Orders => self.orders.view(data),
+                PaneType::OpenOrders => self.open_orders.view(data, self.pair()),
                 PaneType::Calculator => self.calculator.view().map(DashboardMessage::from),
+                PaneType::Log => self.log.view(data),
             }))
             .title_bar(title_bar)
             .style(if is_focused {
@@ -319,7 +758,41 @@ impl DashboardView {
         .spacing(10)
         .on_click(DashboardMessage::Clicked)
         .on_drag(DashboardMessage::Dragged)
-        .on_resize(10, DashboardMessage::Resized)
-        .into()
+        .on_resize(10, DashboardMessage::Resized);
+
+        column![toasts(&self.toasts, DashboardMessage::ToastClosed), panes]
+            .spacing(8)
+            .into()
     }
 }
+
+/// Dot colored by feed status, details are shown on hover
+fn feed_indicator<'a>(data: &AppData, stream: &Stream) -> Element<'a, DashboardMessage> {
+    let color = match data.streams.status(stream) {
+        FeedStatus::Live => iced::Color::from_rgb(0.0, 0.8, 0.3),
+        FeedStatus::Connecting => iced::Color::from_rgb(0.6, 0.6, 0.6),
+        FeedStatus::Stale => iced::Color::from_rgb(0.95, 0.7, 0.1),
+        FeedStatus::Down => iced::Color::from_rgb(0.9, 0.2, 0.2),
+    };
+
+    tooltip(
+        text('\u{F287}')
+            .font(Font::with_name("bootstrap-icons"))
+            .size(8)
+            .style(color),
+        text(data.streams.summary(stream)).size(12),
+        tooltip::Position::Bottom,
+    )
+    .style(theme::Container::Box)
+    .padding(6)
+    .into()
+}
+
+fn unknown_market(name: String) -> Command<Message> {
+    dispatch_err("market", format!("unknown market {name}"))
+}
+
+fn dispatch_err(source: &str, message: String) -> Command<Message> {
+    let source = source.to_owned();
+    Command::perform(async {}, move |_| Message::DispatchErr((source, message)))
+}
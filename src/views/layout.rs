@@ -0,0 +1,241 @@
+//! Serializable dashboard layout
+//!
+//! Mirrors `pane_grid` tree, so it can be stored in config and turned back into grid state.
+
+use iced::widget::pane_grid::{self, Configuration, Node};
+use serde::{Deserialize, Serialize};
+
+use super::dashboard::{Pane, PaneType};
+use super::panes::depth::DEFAULT_DEPTH_RANGE;
+use crate::{api::DEFAULT_TIMEFRAME, indicators::Indicator};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl From<pane_grid::Axis> for Axis {
+    fn from(value: pane_grid::Axis) -> Self {
+        match value {
+            pane_grid::Axis::Horizontal => Self::Horizontal,
+            pane_grid::Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+impl From<Axis> for pane_grid::Axis {
+    fn from(value: Axis) -> Self {
+        match value {
+            Axis::Horizontal => Self::Horizontal,
+            Axis::Vertical => Self::Vertical,
+        }
+    }
+}
+
+/// Pane tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Layout {
+    Split {
+        axis: Axis,
+        ratio: f32,
+        a: Box<Layout>,
+        b: Box<Layout>,
+    },
+    Pane {
+        kind: PaneType,
+        #[serde(default)]
+        pinned: bool,
+        #[serde(default)]
+        maximized: bool,
+        /// Symbol of pane instance, selected pair is shown when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        symbol: Option<String>,
+        /// Kline interval of chart panes, default one when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeframe: Option<String>,
+        /// Indicators of chart panes
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        indicators: Vec<Indicator>,
+        /// Price range of depth panes in percent, default one when missing
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        depth_range: Option<f64>,
+    },
+}
+
+impl Layout {
+    pub(crate) fn split(axis: Axis, ratio: f32, a: Layout, b: Layout) -> Self {
+        Self::Split {
+            axis,
+            ratio,
+            a: Box::new(a),
+            b: Box::new(b),
+        }
+    }
+
+    pub(crate) fn pane(kind: PaneType) -> Self {
+        Self::Pane {
+            kind,
+            pinned: false,
+            maximized: false,
+            symbol: None,
+            timeframe: None,
+            indicators: Vec::new(),
+            depth_range: None,
+        }
+    }
+
+    /// Snapshot of grid state
+    pub(crate) fn from_state(state: &pane_grid::State<Pane>) -> Self {
+        Self::from_node(state, state.layout())
+    }
+
+    fn from_node(state: &pane_grid::State<Pane>, node: &Node) -> Self {
+        match node {
+            Node::Split {
+                axis, ratio, a, b, ..
+            } => Self::split(
+                (*axis).into(),
+                *ratio,
+                Self::from_node(state, a),
+                Self::from_node(state, b),
+            ),
+            Node::Pane(pane) => {
+                let p = state.get(*pane).expect("layout pane exists in state");
+                Self::Pane {
+                    kind: p.id,
+                    pinned: p.is_pinned,
+                    maximized: state.maximized() == Some(*pane),
+                    symbol: p.symbol.clone(),
+                    timeframe: (p.id == PaneType::Chart && p.timeframe != DEFAULT_TIMEFRAME)
+                        .then(|| p.timeframe.clone()),
+                    indicators: p.indicators.clone(),
+                    depth_range: (p.id == PaneType::Depth && p.depth_range != DEFAULT_DEPTH_RANGE)
+                        .then_some(p.depth_range),
+                }
+            }
+        }
+    }
+
+    /// Build grid state, maximizing pane if one was maximized
+    pub(crate) fn into_state(self) -> pane_grid::State<Pane> {
+        let maximized = self.maximized_flags().position(|maximized| maximized);
+        let mut state = pane_grid::State::with_configuration(self.into_configuration());
+
+        // grid keeps panes in same depth first order as configuration
+        if let Some(pane) = maximized.and_then(|i| panes_in_order(state.layout()).nth(i)) {
+            state.maximize(pane);
+        }
+
+        state
+    }
+
+    fn into_configuration(self) -> Configuration<Pane> {
+        match self {
+            Self::Split { axis, ratio, a, b } => Configuration::Split {
+                axis: axis.into(),
+                ratio,
+                a: Box::new(a.into_configuration()),
+                b: Box::new(b.into_configuration()),
+            },
+            Self::Pane {
+                kind,
+                pinned,
+                symbol,
+                timeframe,
+                indicators,
+                depth_range,
+                ..
+            } => Configuration::Pane(
+                Pane::new(kind, pinned, symbol, timeframe)
+                    .with_indicators(indicators)
+                    .with_depth_range(depth_range),
+            ),
+        }
+    }
+
+    /// Depth first maximized flags of panes
+    fn maximized_flags(&self) -> Box<dyn Iterator<Item = bool> + '_> {
+        match self {
+            Self::Split { a, b, .. } => Box::new(a.maximized_flags().chain(b.maximized_flags())),
+            Self::Pane { maximized, .. } => Box::new(std::iter::once(*maximized)),
+        }
+    }
+}
+
+fn panes_in_order(node: &Node) -> Box<dyn Iterator<Item = pane_grid::Pane> + '_> {
+    match node {
+        Node::Split { a, b, .. } => Box::new(panes_in_order(a).chain(panes_in_order(b))),
+        Node::Pane(pane) => Box::new(std::iter::once(*pane)),
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        use Axis::{Horizontal, Vertical};
+        use PaneType::*;
+
+        let p = Layout::pane;
+        let split = Layout::split;
+
+        split(
+            Horizontal,
+            0.65,
+            split(
+                Vertical,
+                0.15,
+                split(Horizontal, 0.6, p(Prices), p(Balances)),
+                split(
+                    Vertical,
+                    0.5,
+                    p(Chart),
+                    split(
+                        Vertical,
+                        0.6,
+                        split(Horizontal, 0.33, p(Market), p(Trades)),
+                        p(Book),
+                    ),
+                ),
+            ),
+            split(
+                Vertical,
+                0.7,
+                split(Horizontal, 0.5, p(OpenOrders), p(Orders)),
+                split(Horizontal, 0.5, p(Calculator), p(Log)),
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::IndicatorKind;
+
+    #[test]
+    fn layout_survives_grid_round_trip() {
+        let mut layout = Layout::default();
+        if let Layout::Split { b, .. } = &mut layout {
+            if let Layout::Split { b, .. } = b.as_mut() {
+                **b = Layout::Pane {
+                    kind: PaneType::Chart,
+                    pinned: true,
+                    maximized: true,
+                    symbol: Some("ETHUSDT".to_owned()),
+                    timeframe: Some("1h".to_owned()),
+                    indicators: vec![Indicator::new(IndicatorKind::Macd)],
+                    depth_range: None,
+                };
+            }
+        }
+
+        let json = serde_json::to_string(&layout).unwrap();
+        let restored: Layout = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, layout);
+
+        let state = restored.into_state();
+        assert!(state.maximized().is_some());
+        assert_eq!(Layout::from_state(&state), layout);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod components;
 pub mod dashboard;
+pub mod layout;
+pub mod palette;
 pub mod panes;
 pub mod settings;
@@ -0,0 +1,195 @@
+//! Fuzzy finder over actions and markets
+
+use iced::{
+    widget::{button, column, container, row, text, text_input, Column, Space},
+    Color, Element, Length,
+};
+
+use crate::{
+    data::Symbols,
+    keymap::{Action, Keymap},
+    message::Message,
+};
+
+/// Maximum number of matches shown
+const MAX_ENTRIES: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PaletteEntry {
+    Action(Action),
+    /// Select market in dashboard
+    Symbol(String),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum PaletteMessage {
+    QueryInput(String),
+    /// Run selected entry
+    Submitted,
+    Picked(PaletteEntry),
+}
+
+pub(crate) struct CommandPalette {
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    pub(crate) fn new() -> Self {
+        Self {
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    pub(crate) fn input_id() -> text_input::Id {
+        text_input::Id::new("command-palette")
+    }
+
+    /// Best matches for current query, actions come before markets on equal score
+    fn entries(&self, symbols: &Symbols) -> Vec<(PaletteEntry, String)> {
+        let actions = Action::ALL
+            .into_iter()
+            .map(|action| (PaletteEntry::Action(action), action.to_string()));
+        let markets = symbols.trading().map(|info| {
+            (
+                PaletteEntry::Symbol(info.symbol.clone()),
+                info.symbol.clone(),
+            )
+        });
+
+        let mut matches: Vec<_> = actions
+            .chain(markets)
+            .filter_map(|(entry, label)| {
+                fuzzy_score(&self.query, &label).map(|score| (score, entry, label))
+            })
+            .collect();
+        matches.sort_by(|(a_score, a, a_label), (b_score, b, b_label)| {
+            let is_market = |e: &PaletteEntry| matches!(e, PaletteEntry::Symbol(_));
+            (a_score, is_market(a), a_label).cmp(&(b_score, is_market(b), b_label))
+        });
+
+        matches
+            .into_iter()
+            .take(MAX_ENTRIES)
+            .map(|(_, entry, label)| (entry, label))
+            .collect()
+    }
+
+    /// Move highlighted entry, wrapping around
+    pub(crate) fn move_selection(&mut self, symbols: &Symbols, delta: isize) {
+        let len = self.entries(symbols).len() as isize;
+        if len > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Entry to run once user made a choice
+    pub(crate) fn update(
+        &mut self,
+        message: PaletteMessage,
+        symbols: &Symbols,
+    ) -> Option<PaletteEntry> {
+        match message {
+            PaletteMessage::QueryInput(query) => {
+                self.query = query;
+                self.selected = 0;
+                None
+            }
+            PaletteMessage::Submitted => self
+                .entries(symbols)
+                .into_iter()
+                .nth(self.selected)
+                .map(|(entry, _)| entry),
+            PaletteMessage::Picked(entry) => Some(entry),
+        }
+    }
+
+    pub(crate) fn view<'a>(
+        &'a self,
+        keymap: &'a Keymap,
+        symbols: &Symbols,
+    ) -> Element<'a, Message> {
+        let input = text_input("Type an action or a market", &self.query)
+            .id(Self::input_id())
+            .on_input(|s| PaletteMessage::QueryInput(s).into())
+            .on_submit(PaletteMessage::Submitted.into())
+            .size(14)
+            .padding(8);
+
+        let entries = self
+            .entries(symbols)
+            .into_iter()
+            .enumerate()
+            .map(|(i, (entry, label))| {
+                let hint = match &entry {
+                    PaletteEntry::Action(action) => keymap.binding(*action).unwrap_or_default(),
+                    PaletteEntry::Symbol(_) => "market",
+                };
+
+                button(
+                    row![
+                        text(label).size(14),
+                        Space::new(Length::Fill, 1),
+                        text(hint).size(12).style(Color::from_rgb(0.6, 0.6, 0.6)),
+                    ]
+                    .align_items(iced::Alignment::Center),
+                )
+                .width(Length::Fill)
+                .style(if i == self.selected {
+                    iced::theme::Button::Primary
+                } else {
+                    iced::theme::Button::Text
+                })
+                .on_press(PaletteMessage::Picked(entry).into())
+                .into()
+            });
+
+        container(column![input, Column::with_children(entries).spacing(2)].spacing(8))
+            .padding(12)
+            .width(Length::Fill)
+            .style(container::Appearance {
+                background: Some(iced::Background::Color(Color::from_rgb(0.1, 0.1, 0.1))),
+                border: iced::Border {
+                    radius: 16.0.into(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+}
+
+/// Case insensitive subsequence match, lower score is better
+///
+/// Every skipped character adds to the score, so contiguous and early matches win.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<usize> {
+    let candidate = candidate.to_lowercase();
+    let mut chars = candidate.chars().enumerate();
+    let mut score = 0;
+    let mut last = None;
+
+    for q in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let (i, _) = chars.find(|(_, c)| *c == q)?;
+        score += match last {
+            Some(last) => i - last - 1,
+            None => i,
+        };
+        last = Some(i);
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_score_prefers_contiguous_matches() {
+        assert_eq!(fuzzy_score("", "BTCUSDT"), Some(0));
+        assert_eq!(fuzzy_score("btc", "BTCUSDT"), Some(0));
+        assert_eq!(fuzzy_score("xyz", "BTCUSDT"), None);
+        assert!(fuzzy_score("eth", "ETHUSDT") < fuzzy_score("eth", "BETHETH"));
+    }
+}
@@ -18,12 +18,14 @@ impl BookPane {
         Self {}
     }
 
-    pub(crate) fn view<'a>(&'a self, data: &'a AppData) -> Element<'a, DashboardMessage> {
-        let book = &data.book;
-
-        if book.1.is_empty() {
+    pub(crate) fn view<'a>(
+        &'a self,
+        data: &'a AppData,
+        symbol: &str,
+    ) -> Element<'a, DashboardMessage> {
+        let Some(book) = data.books.get(symbol).filter(|b| !b.is_empty()) else {
             return loader!().into();
-        }
+        };
 
         let header = row![
             tb("Price").width(Length::Fill),
@@ -32,20 +34,21 @@ impl BookPane {
         ]
         .spacing(10);
 
+        // best ask goes last, right above spread
+        let mut asks: Vec<_> = book.asks().take(12).collect();
+        asks.reverse();
+
         let ask_rows = Column::with_children(
-            book.2
-                .iter()
-                .rev()
-                .take(12)
+            asks.into_iter()
                 .map(|(price, quantity)| {
                     row![
-                        t(format!("{:.2}", price.parse::<f64>().unwrap()))
+                        t(format!("{price:.2}"))
                             .width(Length::FillPortion(1))
                             .style(iced::Color::from_rgb(1.0, 0.0, 0.0)),
                         t(format!("{quantity:.4}"))
                             .width(Length::FillPortion(1))
                             .style(h2c("B7BDB7").unwrap()),
-                        t(format!("{:.2}", price.parse::<f64>().unwrap() * quantity))
+                        t(format!("{:.2}", price * quantity))
                             .style(h2c("B7BDB7").unwrap())
                             .width(Length::FillPortion(1)),
                     ]
@@ -55,19 +58,17 @@ impl BookPane {
         );
 
         let bid_rows = Column::with_children(
-            book.1
-                .iter()
-                .rev()
+            book.bids()
                 .take(9)
                 .map(|(price, quantity)| {
                     row![
-                        t(format!("{:.2}", price.parse::<f64>().unwrap()))
+                        t(format!("{price:.2}"))
                             .width(Length::FillPortion(1))
                             .style(iced::Color::from_rgb(0.0, 1.0, 0.0)),
                         t(format!("{quantity:.2}"))
                             .width(Length::FillPortion(1))
                             .style(h2c("B7BDB7").unwrap()),
-                        t(format!("{:.2}", price.parse::<f64>().unwrap() * quantity))
+                        t(format!("{:.2}", price * quantity))
                             .width(Length::FillPortion(1))
                             .style(h2c("B7BDB7").unwrap())
                     ]
@@ -79,15 +80,12 @@ impl BookPane {
         let content = column![
             header,
             ask_rows,
-            tb(format!(
-                "${}",
-                book.1
-                    .iter()
-                    .next_back()
-                    .unwrap_or((&String::new(), &0.0))
-                    .0
-            ))
-            .style(iced::Color::WHITE),
+            row![
+                tb(format!("${:.2}", book.mid().unwrap_or_default()))
+                    .style(iced::Color::WHITE)
+                    .width(Length::Fill),
+                tb(format!("spread {:.2}", book.spread().unwrap_or_default())).width(Length::Fill),
+            ],
             bid_rows
         ]
         .padding([2, 12])
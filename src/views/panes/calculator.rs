@@ -1,9 +1,13 @@
 #[cfg(not(any(feature = "calculator_meval", feature = "calculator_k")))]
 compile_error!("no calculator backend selected");
 
-use crate::{data::AppData, theme::h2c, views::components::better_btn::GreenBtn};
+use crate::{
+    data::AppData,
+    exchange::{Order, OrderSide},
+    theme::h2c,
+    views::components::better_btn::GreenBtn,
+};
 
-use binance::rest_model::Order;
 use iced::{
     widget::{
         button, column, container, text,
@@ -126,7 +130,7 @@ impl CalculatorPane {
 pub(crate) fn order_value(order: &Order, price_now: f64) -> f64 {
     let price = order.price;
     let qty = order.executed_qty;
-    if order.side == binance::rest_model::OrderSide::Buy {
+    if order.side == OrderSide::Buy {
         qty * (price_now - price)
     } else {
         qty * (price - price_now)
@@ -135,7 +139,7 @@ pub(crate) fn order_value(order: &Order, price_now: f64) -> f64 {
 
 #[cfg(feature = "calculator_k")]
 mod calc_k {
-    use crate::{api::Client, data::AppData, views::panes::calculator::order_value};
+    use crate::{data::AppData, views::panes::calculator::order_value};
 
     use ngnk::{kinit, CK, K0};
 
@@ -152,7 +156,7 @@ mod calc_k {
             let mut keys = String::new();
             let mut values = String::new();
             for (key, val) in data.prices.all().take(250) {
-                if let Some([base, _]) = Client::split_symbol(key) {
+                if let Some([base, _]) = data.symbols.split(key) {
                     let filtered: String = base.chars().filter(|c| c.is_alphabetic()).collect();
                     if !filtered.is_empty() {
                         keys.push_str(&format!("`\"{filtered}\""));
@@ -1,79 +1,788 @@
+use std::{
+    cell::Cell,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use iced::event;
+use iced::mouse;
 use iced::widget::button;
+use iced::widget::canvas;
 use iced::widget::column;
 use iced::widget::container;
+use iced::widget::pane_grid;
+use iced::widget::pick_list;
 use iced::widget::row;
+use iced::widget::text_input;
 use iced::widget::Row;
 use iced::widget::Space;
 use iced::Element;
 use iced::Length;
+use iced::Point;
+use plotters::coord::types::{RangedCoordf64, RangedCoordi64};
+use plotters::coord::ReverseCoordTranslate;
+use plotters::coord::Shift;
 use plotters::prelude::*;
 use plotters::style::colors;
 use plotters::style::IntoFont;
 use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend};
-use ringbuf::ring_buffer::RbBase;
-use ringbuf::Rb;
 
-use super::orders::tb;
-use crate::data::AppData;
+use super::orders::{t, tb};
+use crate::data::{AppData, Candles, ChartKey};
+use crate::drawings::{Anchor, Drawing, DrawingTool};
+use crate::exchange::{Kline, Order, OrderSide};
+use crate::indicators::{Indicator, IndicatorKind};
 use crate::views::components::loading::loader;
+use crate::views::dashboard::Pane;
 use crate::views::{components::better_btn::GreenBtn, dashboard::DashboardMessage};
 
+/// Width of price and volume labels
+const Y_LABEL_AREA: u32 = 70;
+
+/// Colors of indicator lines, picked in order
+const LINE_COLORS: [RGBColor; 6] = [
+    RGBColor(240, 200, 60),
+    RGBColor(80, 170, 250),
+    RGBColor(220, 110, 220),
+    RGBColor(250, 140, 60),
+    RGBColor(120, 220, 200),
+    RGBColor(200, 200, 200),
+];
+
+const DRAWING_COLOR: RGBColor = RGBColor(90, 160, 255);
+
+type TimeChart<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordi64, RangedCoordf64>>;
+
 pub(crate) struct ChartPane {}
 
-struct PriceChart<'a>(&'a AppData);
+/// Fewest candles zoom can show
+const MIN_VISIBLE: usize = 10;
 
-impl Chart<DashboardMessage> for PriceChart<'_> {
-    type State = ();
+/// Distance in pixels at which order line can be grabbed
+const GRAB_DISTANCE: f32 = 4.0;
 
-    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, mut builder: ChartBuilder<DB>) {
-        const LINE_COLOR: RGBColor = colors::GREEN;
+/// Older history of the same oldest candle is requested again after this, when last request
+/// failed or there was nothing older
+const HISTORY_RETRY: Duration = Duration::from_secs(10);
 
-        let (min, max) = self
-            .0
-            .price_chart
-            .iter()
-            .fold((f32::MAX, f32::MIN), |acc, &x| {
-                (acc.0.min(x as f32), acc.1.max(x as f32))
-            });
+/// Candlesticks with volume bars and oscillator panels below them
+struct CandleChart<'a> {
+    pane: pane_grid::Pane,
+    key: ChartKey,
+    candles: &'a Candles,
+    indicators: &'a [Indicator],
+    /// Filled and open orders of chart symbol
+    fills: Vec<&'a Order>,
+    open_orders: Vec<&'a Order>,
+    entry: Option<f64>,
+    /// Decimals of symbol tick size, for price labels
+    decimals: usize,
+    drawings: &'a [Drawing],
+    tool: Option<DrawingTool>,
+    annotation: &'a str,
+}
 
-        let mut chart = builder
-            .x_label_area_size(0_i32)
-            .y_label_area_size(70_i32)
-            .margin(0_i32)
-            .build_cartesian_2d(0..self.0.price_chart.len(), min..max)
-            .expect("Failed to build chart");
+/// Zoom, pan and cursor of one chart
+#[derive(Debug, Default)]
+struct ChartState {
+    /// Number of candles shown, all loaded ones when `None`
+    visible: Option<usize>,
+    /// Candles hidden right of view, zero follows latest candle
+    offset: usize,
+    /// Cursor x and offset when drag started
+    drag: Option<(f32, usize)>,
+    cursor: Option<Point>,
+    /// Open time of oldest candle when older history was requested and time of request, so it
+    /// is requested once per page
+    requested: Option<(i64, Instant)>,
+    /// Id and new price of order line being dragged
+    order_drag: Option<(u64, f64)>,
+    /// Shown price range and plot height, written on draw for mapping cursor to price
+    price_axis: Cell<Option<(f64, f64, f32)>>,
+    /// Shown time range and plot width
+    time_axis: Cell<Option<(i64, i64, f32)>>,
+    /// First point of drawing being dragged
+    draft: Option<Anchor>,
+}
 
-        chart
-            .configure_mesh()
-            .disable_mesh()
-            .bold_line_style(plotters::style::colors::full_palette::GREY_600.stroke_width(3))
-            .light_line_style(plotters::style::colors::full_palette::GREY_800.stroke_width(3))
-            .axis_style(
-                ShapeStyle::from(plotters::style::colors::full_palette::GREY_500).stroke_width(0),
-            )
-            .y_max_light_lines(2)
-            .y_labels(20)
-            .y_label_style(
-                ("monospace", 12)
-                    .into_font()
-                    .color(&plotters::style::colors::WHITE)
-                    .transform(FontTransform::Rotate90),
-            )
-            .x_max_light_lines(30)
-            .x_labels(3)
-            .draw()
-            .unwrap();
+impl ChartState {
+    /// Indices of shown candles
+    fn window(&self, len: usize) -> Range<usize> {
+        let visible = self.visible.unwrap_or(len).min(len);
+        let end = len - self.offset.min(len - visible);
+        end - visible..end
+    }
+
+    fn price_at(&self, y: f32) -> Option<f64> {
+        let (min, max, height) = self.price_axis.get()?;
+        Some(max - f64::from(y / height) * (max - min))
+    }
+
+    fn y_of(&self, price: f64) -> Option<f32> {
+        let (min, max, height) = self.price_axis.get()?;
+        Some(((max - price) / (max - min)) as f32 * height)
+    }
+
+    fn x_of(&self, time: i64) -> Option<f32> {
+        let (start, end, width) = self.time_axis.get()?;
+        Some(Y_LABEL_AREA as f32 + (time - start) as f32 / (end - start).max(1) as f32 * width)
+    }
+
+    /// Chart point under cursor
+    fn anchor_at(&self, point: Point) -> Option<Anchor> {
+        let (start, end, width) = self.time_axis.get()?;
+        let fraction = f64::from((point.x - Y_LABEL_AREA as f32) / width);
+
+        Some(Anchor {
+            time: start + (fraction * (end - start) as f64) as i64,
+            price: self.price_at(point.y)?,
+        })
+    }
+
+    fn project(&self, anchor: Anchor) -> Option<(f32, f32)> {
+        Some((self.x_of(anchor.time)?, self.y_of(anchor.price)?))
+    }
+}
 
+/// Time label, precision depends on shown time span
+fn time_label(ms: i64, span: i64) -> String {
+    const DAY: i64 = 24 * 60 * 60 * 1000;
+
+    let format = if span > 3 * DAY { "%m-%d" } else { "%H:%M" };
+    chrono::DateTime::from_timestamp_millis(ms)
+        .map(|t| t.with_timezone(&chrono::Local).format(format).to_string())
+        .unwrap_or_default()
+}
+
+/// Range covering all defined values
+fn value_range<'a>(values: impl Iterator<Item = &'a Option<f64>>) -> Range<f64> {
+    let (min, max) = values
+        .flatten()
+        .fold((f64::MAX, f64::MIN), |acc, &v| (acc.0.min(v), acc.1.max(v)));
+
+    if min > max {
+        0.0..1.0
+    } else if min == max {
+        min - 1.0..max + 1.0
+    } else {
+        min..max
+    }
+}
+
+/// Panel sharing time axis with others, only bottom one shows time labels
+fn panel<'a, DB: DrawingBackend>(
+    area: &'a DrawingArea<DB, Shift>,
+    x_range: Range<i64>,
+    y_range: Range<f64>,
+    y_labels: usize,
+    time_labels: Option<i64>,
+) -> TimeChart<'a, DB> {
+    let mut chart = ChartBuilder::on(area)
+        .x_label_area_size(if time_labels.is_some() { 20_i32 } else { 0_i32 })
+        .y_label_area_size(Y_LABEL_AREA)
+        .margin(0_i32)
+        .build_cartesian_2d(x_range, y_range)
+        .expect("Failed to build chart");
+
+    let span = time_labels.unwrap_or_default();
+    let label = move |ms: &i64| time_label(*ms, span);
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .axis_style(ShapeStyle::from(colors::full_palette::GREY_500).stroke_width(0))
+        .x_labels(if time_labels.is_some() { 6 } else { 0 })
+        .x_label_formatter(&label)
+        .x_label_style(("monospace", 12).into_font().color(&colors::WHITE))
+        .y_labels(y_labels)
+        .y_label_style(
+            ("monospace", 10)
+                .into_font()
+                .color(&colors::full_palette::GREY_500),
+        )
+        .draw()
+        .unwrap();
+
+    chart
+}
+
+fn draw_lines<DB: DrawingBackend>(
+    chart: &mut TimeChart<'_, DB>,
+    times: &[i64],
+    lines: &[Vec<Option<f64>>],
+    first_color: usize,
+) {
+    for (i, line) in lines.iter().enumerate() {
+        let color = LINE_COLORS[(first_color + i) % LINE_COLORS.len()];
         chart
             .draw_series(LineSeries::new(
-                self.0
-                    .price_chart
+                times
                     .iter()
-                    .enumerate()
-                    .map(|(x, y)| (x, (*y as f32))),
-                LINE_COLOR,
+                    .zip(line)
+                    .filter_map(|(x, y)| Some((*x, (*y)?))),
+                color.stroke_width(1),
             ))
+            .expect("failed to draw indicator");
+    }
+}
+
+impl CandleChart<'_> {
+    /// Movable order whose line is under cursor
+    fn grabbed_order(&self, state: &ChartState, y: f32) -> Option<&Order> {
+        self.open_orders.iter().copied().find(|o| {
+            o.is_movable()
+                && state
+                    .y_of(o.price)
+                    .is_some_and(|oy| (oy - y).abs() <= GRAB_DISTANCE)
+        })
+    }
+
+    fn request_history(&self, state: &mut ChartState) -> Option<DashboardMessage> {
+        let oldest = self.candles.iter().next()?.open_time;
+        if state
+            .requested
+            .is_some_and(|(time, at)| time == oldest && at.elapsed() < HISTORY_RETRY)
+        {
+            return None;
+        }
+
+        state.requested = Some((oldest, Instant::now()));
+        // keep current view once older candles are prepended
+        state.visible.get_or_insert(self.candles.len());
+        Some(DashboardMessage::HistoryRequested(self.key.clone()))
+    }
+}
+
+impl Chart<DashboardMessage> for CandleChart<'_> {
+    type State = ChartState;
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, _builder: ChartBuilder<DB>) {}
+
+    fn draw_chart<DB: DrawingBackend>(&self, state: &Self::State, root: DrawingArea<DB, Shift>) {
+        let window = state.window(self.candles.len());
+        let candles: Vec<&Kline> = self
+            .candles
+            .iter()
+            .skip(window.start)
+            .take(window.len())
+            .collect();
+        let (Some(first), Some(last)) = (candles.first(), candles.last()) else {
+            return;
+        };
+
+        let x_range = first.open_time..last.close_time;
+        let span = last.close_time - first.open_time;
+        let times: Vec<i64> = candles
+            .iter()
+            .map(|k| (k.open_time + k.close_time) / 2)
+            .collect();
+
+        // indicators need history left of view, so they are computed over all candles
+        let outputs: Vec<_> = self
+            .indicators
+            .iter()
+            .map(|indicator| {
+                let mut output = indicator.compute(self.candles);
+                for line in output.lines.iter_mut().chain(&mut output.histogram) {
+                    *line = line[window.clone()].to_vec();
+                }
+                (indicator.kind, output)
+            })
+            .collect();
+        let oscillators = outputs
+            .iter()
+            .filter(|(kind, _)| !kind.is_overlay())
+            .count();
+
+        // price keeps at least a half of height
+        let height = root.dim_in_pixel().1 as f64;
+        let volume_height = (height * 0.15) as i32;
+        let oscillator_height = (height * 0.35 / oscillators.max(2) as f64) as i32;
+        let price_height =
+            root.dim_in_pixel().1 as i32 - volume_height - oscillator_height * oscillators as i32;
+
+        let (price_area, rest) = root.split_vertically(price_height);
+        let (volume_area, mut rest) = rest.split_vertically(volume_height);
+
+        // overlays decide price range too, so bands are not cut off
+        let overlay_values = outputs
+            .iter()
+            .filter(|(kind, _)| kind.is_overlay())
+            .flat_map(|(_, output)| output.lines.iter().flatten());
+        let price_range = value_range(
+            candles
+                .iter()
+                .flat_map(|k| [Some(k.low), Some(k.high)])
+                .collect::<Vec<_>>()
+                .iter()
+                .chain(overlay_values),
+        );
+
+        state.price_axis.set(Some((
+            price_range.start,
+            price_range.end,
+            price_height as f32,
+        )));
+        let plot_width = price_area.dim_in_pixel().0.saturating_sub(Y_LABEL_AREA);
+        state
+            .time_axis
+            .set(Some((x_range.start, x_range.end, plot_width as f32)));
+        let mut prices = panel(&price_area, x_range.clone(), price_range.clone(), 20, None);
+        let candle_width = (plot_width as f64 / candles.len() as f64 * 0.7).max(1.0) as u32;
+
+        prices
+            .draw_series(candles.iter().zip(&times).map(|(k, x)| {
+                CandleStick::new(
+                    *x,
+                    k.open,
+                    k.high,
+                    k.low,
+                    k.close,
+                    colors::GREEN.filled(),
+                    colors::RED.filled(),
+                    candle_width,
+                )
+            }))
             .expect("failed to draw chart data");
+
+        let mut color = 0;
+        for (_, output) in outputs.iter().filter(|(kind, _)| kind.is_overlay()) {
+            draw_lines(&mut prices, &times, &output.lines, color);
+            color += output.lines.len();
+        }
+
+        self.draw_orders(&mut prices, state, x_range.clone());
+
+        let draft = self.tool.zip(state.draft).and_then(|(tool, start)| {
+            let end = state.anchor_at(state.cursor?)?;
+            Some(tool.build(start, end, self.annotation))
+        });
+        for drawing in self.drawings.iter().chain(&draft) {
+            draw_drawing(&mut prices, drawing, &x_range, &price_range, self.decimals);
+        }
+
+        let max_volume = candles.iter().map(|k| k.volume).fold(0.0, f64::max);
+        let mut volumes = panel(
+            &volume_area,
+            x_range.clone(),
+            0.0..max_volume.max(f64::EPSILON),
+            2,
+            (oscillators == 0).then_some(span),
+        );
+
+        volumes
+            .draw_series(candles.iter().map(|k| {
+                let color = if k.close >= k.open {
+                    colors::GREEN
+                } else {
+                    colors::RED
+                };
+                let gap = (k.close_time - k.open_time) / 6;
+
+                Rectangle::new(
+                    [(k.open_time + gap, 0.0), (k.close_time - gap, k.volume)],
+                    color.mix(0.5).filled(),
+                )
+            }))
+            .expect("failed to draw volume data");
+
+        let panels = outputs.iter().filter(|(kind, _)| !kind.is_overlay());
+        for (i, (kind, output)) in panels.enumerate() {
+            let is_last = i + 1 == oscillators;
+            let area = if is_last {
+                rest.clone()
+            } else {
+                let (area, below) = rest.split_vertically(oscillator_height);
+                rest = below;
+                area
+            };
+
+            let values = output.lines.iter().chain(&output.histogram).flatten();
+            let y_range = match kind {
+                IndicatorKind::Rsi => 0.0..100.0,
+                _ => value_range(values.chain([&Some(0.0)])),
+            };
+            let mut chart = panel(&area, x_range.clone(), y_range, 3, is_last.then_some(span));
+
+            if let Some(histogram) = &output.histogram {
+                let half = (last.close_time - last.open_time) / 3;
+                chart
+                    .draw_series(times.iter().zip(histogram).filter_map(|(x, v)| {
+                        let v = (*v)?;
+                        let color = if v >= 0.0 { colors::GREEN } else { colors::RED };
+                        Some(Rectangle::new(
+                            [(x - half, 0.0), (x + half, v)],
+                            color.mix(0.5).filled(),
+                        ))
+                    }))
+                    .expect("failed to draw histogram");
+            }
+
+            draw_lines(&mut chart, &times, &output.lines, color);
+            color += output.lines.len();
+        }
+
+        if let Some(cursor) = state.cursor.filter(|_| state.drag.is_none()) {
+            draw_crosshair(&root, &prices, &candles, cursor, self.decimals);
+        }
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<DashboardMessage>) {
+        if let canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) = event {
+            state.drag = None;
+
+            if let Some(start) = state.draft.take() {
+                let end = cursor
+                    .position_in(bounds)
+                    .or(state.cursor)
+                    .and_then(|p| state.anchor_at(p));
+                let message = self
+                    .tool
+                    .zip(end.filter(|end| *end != start))
+                    .map(|(tool, end)| {
+                        DashboardMessage::DrawingAdded(
+                            self.pane,
+                            tool.build(start, end, self.annotation),
+                        )
+                    });
+                return (event::Status::Captured, message);
+            }
+
+            if let Some((order_id, price)) = state.order_drag.take() {
+                // orders of chart symbol only, so id is enough here
+                let message = self
+                    .open_orders
+                    .iter()
+                    .find(|o| o.order_id == order_id && o.price != price)
+                    .map(|o| DashboardMessage::OrderMoved(o.symbol.clone(), order_id, price));
+                return (event::Status::Captured, message);
+            }
+        }
+
+        state.cursor = cursor.position_in(bounds);
+        let Some(position) = state.cursor else {
+            return (event::Status::Ignored, None);
+        };
+
+        let len = self.candles.len();
+        let window = state.window(len);
+        let plot_width = (bounds.width - Y_LABEL_AREA as f32).max(1.0);
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                let lines = match delta {
+                    mouse::ScrollDelta::Lines { y, .. } => y,
+                    mouse::ScrollDelta::Pixels { y, .. } => y / 50.0,
+                };
+                let shown = window.len() as f32;
+                let visible = ((shown * (1.0 - 0.1 * lines.clamp(-3.0, 3.0))).round() as usize)
+                    .clamp(MIN_VISIBLE.min(len), len);
+
+                // candle under cursor stays in place
+                let fraction = ((position.x - Y_LABEL_AREA as f32) / plot_width).clamp(0.0, 1.0);
+                let anchor = window.start as f32 + fraction * shown;
+                let start = (anchor - fraction * visible as f32).max(0.0) as usize;
+                let end = (start + visible).min(len);
+                state.visible = Some(visible);
+                state.offset = len - end;
+
+                let message = (lines < 0.0 && visible == len)
+                    .then(|| self.request_history(state))
+                    .flatten();
+                (event::Status::Captured, message)
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left))
+                if self.tool.is_some() =>
+            {
+                let (Some(tool), Some(anchor)) = (self.tool, state.anchor_at(position)) else {
+                    return (event::Status::Captured, None);
+                };
+
+                let message = if !tool.is_single_point() {
+                    state.draft = Some(anchor);
+                    None
+                } else if tool == DrawingTool::Text && self.annotation.trim().is_empty() {
+                    None
+                } else {
+                    let drawing = tool.build(anchor, anchor, self.annotation);
+                    Some(DashboardMessage::DrawingAdded(self.pane, drawing))
+                };
+                (event::Status::Captured, message)
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                // last drawn is on top, so it is removed first
+                let hit = self.drawings.iter().rposition(|drawing| {
+                    drawing
+                        .distance((position.x, position.y), |a| state.project(a))
+                        .is_some_and(|d| d <= GRAB_DISTANCE * 2.0)
+                });
+                let message =
+                    hit.map(|i| DashboardMessage::DrawingRemoved(self.key.symbol.clone(), i));
+                (event::Status::Captured, message)
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                match self.grabbed_order(state, position.y) {
+                    Some(order) => state.order_drag = Some((order.order_id, order.price)),
+                    None => state.drag = Some((position.x, state.offset)),
+                }
+                (event::Status::Captured, None)
+            }
+            canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if state.draft.is_some() {
+                    return (event::Status::Captured, None);
+                }
+                if let Some((order_id, _)) = state.order_drag {
+                    if let Some(price) = state.price_at(position.y) {
+                        state.order_drag = Some((order_id, price));
+                    }
+                    return (event::Status::Captured, None);
+                }
+
+                let Some((x, offset)) = state.drag else {
+                    return (event::Status::Ignored, None);
+                };
+
+                let candle_px = plot_width / window.len().max(1) as f32;
+                let wanted = offset as isize + ((position.x - x) / candle_px).round() as isize;
+                let max = (len - window.len()) as isize;
+                state.offset = wanted.clamp(0, max) as usize;
+
+                let message = (wanted > max)
+                    .then(|| self.request_history(state))
+                    .flatten();
+                (event::Status::Captured, message)
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn mouse_interaction(
+        &self,
+        state: &Self::State,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        let over_order = self.tool.is_none()
+            && cursor
+                .position_in(bounds)
+                .is_some_and(|p| self.grabbed_order(state, p.y).is_some());
+
+        if state.order_drag.is_some() || over_order {
+            return mouse::Interaction::ResizingVertically;
+        }
+
+        match (state.drag, cursor.is_over(bounds)) {
+            (Some(_), _) => mouse::Interaction::Grabbing,
+            (None, true) => mouse::Interaction::Crosshair,
+            (None, false) => mouse::Interaction::Idle,
+        }
+    }
+}
+
+impl CandleChart<'_> {
+    /// Fill markers, open order lines and average entry price
+    fn draw_orders<DB: DrawingBackend>(
+        &self,
+        prices: &mut TimeChart<'_, DB>,
+        state: &ChartState,
+        x_range: Range<i64>,
+    ) {
+        let side_color = |side| match side {
+            OrderSide::Buy => colors::GREEN,
+            OrderSide::Sell => colors::RED,
+        };
+        let font = ("monospace", 11).into_font().color(&colors::WHITE);
+
+        let line = |prices: &mut TimeChart<'_, DB>, price: f64, color: RGBColor, label: String| {
+            let _ = prices.draw_series(LineSeries::new(
+                [(x_range.start, price), (x_range.end, price)],
+                color.mix(0.8).stroke_width(1),
+            ));
+            let _ = prices.draw_series(std::iter::once(Text::new(
+                label,
+                (x_range.start, price),
+                font.clone(),
+            )));
+        };
+
+        let _ = prices.draw_series(
+            self.fills
+                .iter()
+                .map(|o| (o.update_time as i64, o))
+                .filter(|(time, _)| x_range.contains(time))
+                .map(|(time, o)| {
+                    TriangleMarker::new((time, o.fill_price()), 6, side_color(o.side).filled())
+                }),
+        );
+
+        if let Some(entry) = self.entry {
+            let label = format!("entry {entry:.*}", self.decimals);
+            line(prices, entry, LINE_COLORS[0], label);
+        }
+
+        for order in &self.open_orders {
+            let price = match state.order_drag {
+                Some((id, price)) if id == order.order_id => price,
+                _ => order.price,
+            };
+            let label = format!(
+                "{:?} {} @ {price:.*}",
+                order.side,
+                order.orig_qty - order.executed_qty,
+                self.decimals
+            );
+            line(prices, price, side_color(order.side), label);
+        }
+    }
+}
+
+/// Part of segment inside shown time range
+fn clip_segment(a: Anchor, b: Anchor, x_range: &Range<i64>) -> Option<[(i64, f64); 2]> {
+    let (a, b) = if a.time <= b.time { (a, b) } else { (b, a) };
+    if b.time < x_range.start || a.time > x_range.end {
+        return None;
+    }
+
+    let price_at = |time: i64| {
+        if a.time == b.time {
+            a.price
+        } else {
+            a.price + (b.price - a.price) * (time - a.time) as f64 / (b.time - a.time) as f64
+        }
+    };
+    let (start, end) = (a.time.max(x_range.start), b.time.min(x_range.end));
+
+    Some([(start, price_at(start)), (end, price_at(end))])
+}
+
+fn draw_drawing<DB: DrawingBackend>(
+    prices: &mut TimeChart<'_, DB>,
+    drawing: &Drawing,
+    x_range: &Range<i64>,
+    y_range: &Range<f64>,
+    decimals: usize,
+) {
+    let style = DRAWING_COLOR.stroke_width(1);
+    let font = ("monospace", 11).into_font().color(&DRAWING_COLOR);
+    let clamp_price = |price: f64| price.clamp(y_range.start, y_range.end);
+
+    match drawing {
+        Drawing::TrendLine { from, to } => {
+            if let Some(points) = clip_segment(*from, *to, x_range) {
+                let _ = prices.draw_series(LineSeries::new(points, style));
+            }
+        }
+        Drawing::Level { price } => {
+            if y_range.contains(price) {
+                let _ = prices.draw_series(LineSeries::new(
+                    [(x_range.start, *price), (x_range.end, *price)],
+                    style,
+                ));
+                let _ = prices.draw_series(std::iter::once(Text::new(
+                    format!("{price:.decimals$}"),
+                    (x_range.start, *price),
+                    font,
+                )));
+            }
+        }
+        Drawing::Rectangle { from, to } => {
+            let left = from.time.min(to.time).max(x_range.start);
+            let right = from.time.max(to.time).min(x_range.end);
+            if left < right {
+                let corners = [
+                    (left, clamp_price(from.price)),
+                    (right, clamp_price(to.price)),
+                ];
+                let _ = prices.draw_series([
+                    Rectangle::new(corners, DRAWING_COLOR.mix(0.15).filled()),
+                    Rectangle::new(corners, style),
+                ]);
+            }
+        }
+        Drawing::Fibonacci { from, to } => {
+            let left = from.time.min(to.time).max(x_range.start);
+            if left >= x_range.end {
+                return;
+            }
+
+            for (ratio, price) in
+                Drawing::fib_levels(*from, *to).filter(|(_, p)| y_range.contains(p))
+            {
+                let _ = prices.draw_series(LineSeries::new(
+                    [(left, price), (x_range.end, price)],
+                    DRAWING_COLOR.mix(0.7).stroke_width(1),
+                ));
+                let _ = prices.draw_series(std::iter::once(Text::new(
+                    format!("{ratio} ({price:.decimals$})"),
+                    (left, price),
+                    font.clone(),
+                )));
+            }
+        }
+        Drawing::Text { at, text } => {
+            if x_range.contains(&at.time) && y_range.contains(&at.price) {
+                let _ = prices.draw_series(std::iter::once(Text::new(
+                    text.clone(),
+                    (at.time, at.price),
+                    ("monospace", 13).into_font().color(&DRAWING_COLOR),
+                )));
+            }
+        }
+    }
+}
+
+/// Lines through cursor with OHLCV of candle under it
+fn draw_crosshair<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    prices: &TimeChart<'_, DB>,
+    candles: &[&Kline],
+    cursor: Point,
+    decimals: usize,
+) {
+    let (width, height) = root.dim_in_pixel();
+    let (x, y) = (cursor.x as i32, cursor.y as i32);
+    if x < Y_LABEL_AREA as i32 {
+        return;
+    }
+
+    let style = colors::full_palette::GREY_500.stroke_width(1);
+    let font = ("monospace", 12).into_font().color(&colors::WHITE);
+
+    let _ = root.draw(&PathElement::new(vec![(x, 0), (x, height as i32)], style));
+
+    let Some((time, price)) = prices.as_coord_spec().reverse_translate((x, y)) else {
+        return;
+    };
+
+    if y < prices.plotting_area().dim_in_pixel().1 as i32 {
+        let _ = root.draw(&PathElement::new(vec![(0, y), (width as i32, y)], style));
+        let label = format!("{price:.decimals$}");
+        let _ = root.draw(&Text::new(label, (2, y - 14), font.clone()));
+    }
+
+    if let Some(k) = candles
+        .iter()
+        .find(|k| (k.open_time..=k.close_time).contains(&time))
+    {
+        let readout = format!(
+            "{}  O {:.*}  H {:.*}  L {:.*}  C {:.*}  V {:.2}",
+            time_label(k.open_time, 0),
+            decimals,
+            k.open,
+            decimals,
+            k.high,
+            decimals,
+            k.low,
+            decimals,
+            k.close,
+            k.volume
+        );
+        let _ = root.draw(&Text::new(readout, (Y_LABEL_AREA as i32 + 6, 4), font));
     }
 }
 
@@ -82,25 +791,157 @@ impl ChartPane {
         Self {}
     }
 
-    pub(crate) fn view<'a>(&'a self, data: &'a AppData) -> Element<'a, DashboardMessage> {
-        if data.price_chart.is_empty() {
+    /// Indicator list with editable parameters
+    fn indicator_controls<'a>(
+        &self,
+        id: pane_grid::Pane,
+        pane: &'a Pane,
+    ) -> Row<'a, DashboardMessage> {
+        let add = pick_list(
+            IndicatorKind::ALL.as_slice(),
+            None::<IndicatorKind>,
+            move |kind| DashboardMessage::IndicatorAdded(id, kind),
+        )
+        .placeholder("+ Indicator")
+        .text_size(12)
+        .padding([2, 4]);
+
+        let active = pane
+            .indicators
+            .iter()
+            .zip(&pane.param_inputs)
+            .enumerate()
+            .map(|(i, (indicator, inputs))| {
+                let params = indicator.kind.params().iter().zip(inputs).enumerate().map(
+                    |(param, ((name, _), input))| {
+                        text_input(name, input)
+                            .on_input(move |s| {
+                                DashboardMessage::IndicatorParamInput(id, i, param, s)
+                            })
+                            .size(12)
+                            .padding([0, 2])
+                            .width(36)
+                            .into()
+                    },
+                );
+
+                row![t(indicator.kind.to_string()).size(12)]
+                    .extend(params)
+                    .push(
+                        button(t("x").size(12))
+                            .padding([0, 4])
+                            .style(iced::theme::Button::Text)
+                            .on_press(DashboardMessage::IndicatorRemoved(id, i)),
+                    )
+                    .spacing(3)
+                    .align_items(iced::Alignment::Center)
+                    .into()
+            });
+
+        row![add]
+            .extend(active)
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+    }
+
+    /// Drawing tools, right click on chart removes drawing under cursor
+    fn drawing_controls<'a>(
+        &self,
+        id: pane_grid::Pane,
+        symbol: &str,
+        pane: &'a Pane,
+        has_drawings: bool,
+    ) -> Row<'a, DashboardMessage> {
+        let tools = DrawingTool::ALL.map(|tool| {
+            let selected = pane.drawing_tool == Some(tool);
+            button(t(tool.to_string()).size(12))
+                .padding([2, 6])
+                .style(if selected {
+                    iced::theme::Button::Custom(Box::new(GreenBtn {}))
+                } else {
+                    iced::theme::Button::Text
+                })
+                .on_press(DashboardMessage::DrawingToolSelected(
+                    id,
+                    (!selected).then_some(tool),
+                ))
+                .into()
+        });
+
+        let annotation = (pane.drawing_tool == Some(DrawingTool::Text)).then(|| {
+            text_input("Annotation", &pane.annotation)
+                .on_input(move |s| DashboardMessage::AnnotationInput(id, s))
+                .size(12)
+                .padding([0, 2])
+                .width(100)
+        });
+        let clear = has_drawings.then(|| {
+            button(t("Clear").size(12))
+                .padding([2, 6])
+                .style(iced::theme::Button::Text)
+                .on_press(DashboardMessage::DrawingsCleared(symbol.to_owned()))
+        });
+
+        Row::with_children(tools)
+            .push_maybe(annotation)
+            .push_maybe(clear)
+            .spacing(2)
+            .align_items(iced::Alignment::Center)
+    }
+
+    /// `id` is grid pane this chart is shown in
+    pub(crate) fn view<'a>(
+        &'a self,
+        data: &'a AppData,
+        id: pane_grid::Pane,
+        key: &ChartKey,
+        pane: &'a Pane,
+        drawings: &'a [Drawing],
+    ) -> Element<'a, DashboardMessage> {
+        let Some(candles) = data.price_charts.get(key).filter(|c| !c.is_empty()) else {
             return loader!().into();
-        }
+        };
+
+        let of_symbol = |orders: &'a [Order]| -> Vec<&'a Order> {
+            orders.iter().filter(|o| o.symbol == key.symbol).collect()
+        };
 
         let btns = Row::with_children(
             ["1m", "5m", "30m", "1h", "1d"]
                 .map(|t| {
                     button(tb(t).style(iced::Color::WHITE).size(12))
-                        .on_press(DashboardMessage::TimeframeChanged(t.into()))
+                        .on_press(DashboardMessage::TimeframeChanged(id, t.into()))
                         .padding(8)
-                        .style(iced::theme::Button::Custom(Box::new(GreenBtn {})))
+                        .style(if key.timeframe == t {
+                            iced::theme::Button::Custom(Box::new(GreenBtn {}))
+                        } else {
+                            iced::theme::Button::Text
+                        })
                 })
                 .map(Element::from),
         )
         .spacing(4);
 
         container(column![
-            ChartWidget::new(PriceChart(data)),
+            row![
+                self.indicator_controls(id, pane),
+                Space::new(Length::Fill, 0),
+                self.drawing_controls(id, &key.symbol, pane, !drawings.is_empty()),
+            ]
+            .align_items(iced::Alignment::Center),
+            ChartWidget::new(CandleChart {
+                pane: id,
+                key: key.clone(),
+                candles,
+                indicators: &pane.indicators,
+                fills: of_symbol(&data.orders),
+                open_orders: of_symbol(&data.open_orders),
+                entry: data.average_entry(&key.symbol),
+                decimals: data.symbols.price_decimals(&key.symbol),
+                drawings,
+                tool: pane.drawing_tool,
+                annotation: &pane.annotation,
+            }),
             row![
                 Space::new(Length::Fill, 0),
                 btns,
@@ -0,0 +1,312 @@
+use iced::event;
+use iced::mouse;
+use iced::widget::button;
+use iced::widget::canvas;
+use iced::widget::column;
+use iced::widget::container;
+use iced::widget::pane_grid;
+use iced::widget::row;
+use iced::widget::Row;
+use iced::widget::Space;
+use iced::Element;
+use iced::Length;
+use iced::Point;
+use plotters::coord::ReverseCoordTranslate;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::colors;
+use plotters::style::IntoFont;
+use plotters_iced::{Chart, ChartBuilder, ChartWidget, DrawingBackend};
+
+use super::orders::{t, tb};
+use crate::data::{AppData, OrderBook};
+use crate::views::components::loading::loader;
+use crate::views::dashboard::Pane;
+use crate::views::{components::better_btn::GreenBtn, dashboard::DashboardMessage};
+
+/// Percent of mid price shown on each side, when pane has no own range
+pub(crate) const DEFAULT_DEPTH_RANGE: f64 = 2.0;
+
+/// Ranges offered in pane, percent of mid price
+const DEPTH_RANGES: [f64; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
+
+/// Width of size labels
+const Y_LABEL_AREA: u32 = 60;
+
+pub(crate) struct DepthPane {}
+
+/// Totals of all levels from best price up to `price`
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct DepthPoint {
+    price: f64,
+    size: f64,
+    notional: f64,
+}
+
+/// Cumulative book sides within range around mid, best level first
+#[derive(Debug, PartialEq)]
+struct Depth {
+    mid: f64,
+    bids: Vec<DepthPoint>,
+    asks: Vec<DepthPoint>,
+}
+
+impl Depth {
+    /// `range` is percent of mid on each side
+    fn new(book: &OrderBook, range: f64) -> Option<Self> {
+        let mid = book.mid()?;
+        let (low, high) = (mid * (1.0 - range / 100.0), mid * (1.0 + range / 100.0));
+
+        Some(Self {
+            mid,
+            bids: cumulative(book.bids().take_while(|(price, _)| *price >= low)),
+            asks: cumulative(book.asks().take_while(|(price, _)| *price <= high)),
+        })
+    }
+
+    /// Liquidity taken by market order that moves price to `price`
+    fn at(&self, price: f64) -> Option<DepthPoint> {
+        let side = if price < self.mid {
+            self.bids.iter().take_while(|p| p.price >= price).last()
+        } else {
+            self.asks.iter().take_while(|p| p.price <= price).last()
+        };
+
+        Some(DepthPoint { price, ..*side? })
+    }
+}
+
+fn cumulative(levels: impl Iterator<Item = (f64, f64)>) -> Vec<DepthPoint> {
+    let (mut size, mut notional) = (0.0, 0.0);
+
+    levels
+        .map(|(price, quantity)| {
+            size += quantity;
+            notional += price * quantity;
+            DepthPoint {
+                price,
+                size,
+                notional,
+            }
+        })
+        .collect()
+}
+
+/// Step curve from mid out to last level
+fn steps(mid: f64, side: &[DepthPoint]) -> Vec<(f64, f64)> {
+    let mut points = vec![(mid, 0.0)];
+    let mut prev = 0.0;
+    for p in side {
+        points.push((p.price, prev));
+        points.push((p.price, p.size));
+        prev = p.size;
+    }
+    points
+}
+
+struct DepthChart {
+    depth: Depth,
+    range: f64,
+    /// Decimals of symbol tick size, for price labels
+    decimals: usize,
+}
+
+#[derive(Debug, Default)]
+struct DepthState {
+    cursor: Option<Point>,
+}
+
+impl Chart<DashboardMessage> for DepthChart {
+    type State = DepthState;
+
+    fn build_chart<DB: DrawingBackend>(&self, _state: &Self::State, _builder: ChartBuilder<DB>) {}
+
+    fn draw_chart<DB: DrawingBackend>(&self, state: &Self::State, root: DrawingArea<DB, Shift>) {
+        let Depth { mid, bids, asks } = &self.depth;
+        let x_range = mid * (1.0 - self.range / 100.0)..mid * (1.0 + self.range / 100.0);
+        let max_size = bids
+            .last()
+            .into_iter()
+            .chain(asks.last())
+            .map(|p| p.size)
+            .fold(f64::EPSILON, f64::max);
+
+        let mut chart = ChartBuilder::on(&root)
+            .x_label_area_size(20_i32)
+            .y_label_area_size(Y_LABEL_AREA)
+            .margin(4_i32)
+            .build_cartesian_2d(x_range.clone(), 0.0..max_size * 1.05)
+            .expect("Failed to build chart");
+
+        chart
+            .configure_mesh()
+            .disable_mesh()
+            .axis_style(ShapeStyle::from(colors::full_palette::GREY_500).stroke_width(0))
+            .x_labels(5)
+            .x_label_formatter(&|price| format!("{price:.*}", self.decimals))
+            .x_label_style(("monospace", 12).into_font().color(&colors::WHITE))
+            .y_labels(5)
+            .y_label_style(
+                ("monospace", 10)
+                    .into_font()
+                    .color(&colors::full_palette::GREY_500),
+            )
+            .draw()
+            .unwrap();
+
+        for (side, color) in [(bids, colors::GREEN), (asks, colors::RED)] {
+            // extend flat to edge of view, so curve does not end mid air
+            let mut points = steps(*mid, side);
+            if let Some(last) = side.last() {
+                let edge = if last.price < *mid {
+                    x_range.start
+                } else {
+                    x_range.end
+                };
+                points.push((edge, last.size));
+            }
+
+            chart
+                .draw_series(AreaSeries::new(points, 0.0, color.mix(0.2)).border_style(color))
+                .expect("failed to draw depth");
+        }
+
+        let Some(cursor) = state.cursor else {
+            return;
+        };
+        let Some((price, _)) = chart
+            .as_coord_spec()
+            .reverse_translate((cursor.x as i32, cursor.y as i32))
+        else {
+            return;
+        };
+        let Some(point) = self.depth.at(price) else {
+            return;
+        };
+
+        let style = colors::full_palette::GREY_500.stroke_width(1);
+        let _ = chart.draw_series(LineSeries::new(
+            [(price, 0.0), (price, max_size * 1.05)],
+            style,
+        ));
+
+        let distance = (price / mid - 1.0) * 100.0;
+        let readout = format!(
+            "{price:.*} ({distance:+.2}%)  size {:.4}  notional {:.2}",
+            self.decimals, point.size, point.notional
+        );
+        let font = ("monospace", 12).into_font().color(&colors::WHITE);
+        let _ = root.draw(&Text::new(readout, (Y_LABEL_AREA as i32 + 8, 6), font));
+    }
+
+    fn update(
+        &self,
+        state: &mut Self::State,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (event::Status, Option<DashboardMessage>) {
+        if let canvas::Event::Mouse(mouse::Event::CursorMoved { .. }) = event {
+            state.cursor = cursor.position_in(bounds);
+        }
+
+        (event::Status::Ignored, None)
+    }
+}
+
+impl DepthPane {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+
+    pub(crate) fn view<'a>(
+        &'a self,
+        data: &'a AppData,
+        id: pane_grid::Pane,
+        symbol: &str,
+        pane: &'a Pane,
+    ) -> Element<'a, DashboardMessage> {
+        let Some(depth) = data
+            .books
+            .get(symbol)
+            .and_then(|book| Depth::new(book, pane.depth_range))
+        else {
+            return loader!().into();
+        };
+
+        let ranges = Row::with_children(
+            DEPTH_RANGES
+                .map(|range| {
+                    button(tb(format!("{range}%")).style(iced::Color::WHITE).size(12))
+                        .on_press(DashboardMessage::DepthRangeChanged(id, range))
+                        .padding(8)
+                        .style(if pane.depth_range == range {
+                            iced::theme::Button::Custom(Box::new(GreenBtn {}))
+                        } else {
+                            iced::theme::Button::Text
+                        })
+                })
+                .map(Element::from),
+        )
+        .spacing(4);
+
+        let decimals = data.symbols.price_decimals(symbol);
+        let spread = match (depth.bids.first(), depth.asks.first()) {
+            (Some(bid), Some(ask)) => format!("spread {:.*}", decimals, ask.price - bid.price),
+            _ => String::new(),
+        };
+
+        container(column![
+            row![
+                t(format!("mid {:.*}", decimals, depth.mid)).size(12),
+                t(spread).size(12)
+            ]
+            .spacing(12)
+            .padding([0, 8]),
+            ChartWidget::new(DepthChart {
+                depth,
+                range: pane.depth_range,
+                decimals,
+            }),
+            row![
+                Space::new(Length::Fill, 0),
+                ranges,
+                Space::new(Length::Fill, 0)
+            ]
+        ])
+        .padding(2)
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_accumulates_levels_within_range() {
+        let mut book = OrderBook::new("BTCUSDT".to_owned());
+        book.update_bid(99.0, 1.0);
+        book.update_bid(98.0, 2.0);
+        book.update_bid(90.0, 5.0);
+        book.update_ask(101.0, 1.0);
+        book.update_ask(102.0, 3.0);
+
+        let depth = Depth::new(&book, 2.5).unwrap();
+        assert_eq!(depth.mid, 100.0);
+        assert_eq!(depth.bids.len(), 2);
+        assert_eq!(
+            depth.bids[1],
+            DepthPoint {
+                price: 98.0,
+                size: 3.0,
+                notional: 295.0
+            }
+        );
+
+        assert_eq!(depth.at(98.5).map(|p| p.size), Some(1.0));
+        assert_eq!(depth.at(101.5).map(|p| p.notional), Some(101.0));
+        assert_eq!(depth.at(102.0).map(|p| p.size), Some(4.0));
+        assert_eq!(depth.at(100.5), None);
+    }
+}
@@ -0,0 +1,66 @@
+use super::orders::{t, tb};
+use crate::{
+    data::{AppData, Severity},
+    theme::h2c,
+    views::dashboard::DashboardMessage,
+};
+
+use iced::{
+    widget::{column, container, row, scrollable, Column},
+    Element, Length,
+};
+
+/// History of notifications, newest first
+pub(crate) struct LogPane {}
+
+impl LogPane {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+
+    pub(crate) fn view<'a>(&'a self, data: &'a AppData) -> Element<'a, DashboardMessage> {
+        let mut history = data.notifications.history().peekable();
+
+        if history.peek().is_none() {
+            return container(tb("Nothing happened yet"))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let rows = history.map(|n| {
+            let color = match n.severity {
+                Severity::Info => h2c("B7BDB7"),
+                Severity::Warning => h2c("EEBB11"),
+                Severity::Error => h2c("EE1111"),
+            }
+            .unwrap();
+
+            row![
+                t(n.time.format("%m-%d %H:%M:%S").to_string())
+                    .style(h2c("B7BDB7").unwrap())
+                    .width(Length::Fixed(130.0)),
+                t(format!("{:?}", n.severity))
+                    .style(color)
+                    .width(Length::Fixed(70.0)),
+                t(&n.source).width(Length::Fixed(120.0)),
+                t(&n.message).width(Length::Fill),
+            ]
+            .into()
+        });
+
+        column![
+            row![
+                tb("Time").width(Length::Fixed(130.0)),
+                tb("Level").width(Length::Fixed(70.0)),
+                tb("Source").width(Length::Fixed(120.0)),
+                tb("Message").width(Length::Fill),
+            ],
+            scrollable(Column::with_children(rows).spacing(2)),
+        ]
+        .padding([2, 12])
+        .into()
+    }
+}
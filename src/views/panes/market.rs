@@ -1,18 +1,20 @@
+use std::fmt::Display;
+
 use super::orders::tb;
 
 use crate::{
     api::Client,
     data::AppData,
+    exchange::{OcoRequest, OrderRequest, OrderSide, OrderType, TimeInForce},
     message::Message,
     views::components::{
         better_btn::{GreenBtn, RedBtn},
         input::Inp,
     },
-    ws::Websockets,
 };
 
 use iced::{
-    widget::{button, column, container, row, text, text_input, Space},
+    widget::{button, column, container, pick_list, row, text, text_input, Space},
     Alignment, Command, Element, Length,
 };
 
@@ -28,12 +30,98 @@ macro_rules! tin {
     };
 }
 
+/// Order types offered in market pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OrderKind {
+    Market,
+    Limit,
+    PostOnly,
+    StopLossLimit,
+    TakeProfitLimit,
+    Oco,
+}
+
+impl OrderKind {
+    const ALL: [Self; 6] = [
+        Self::Market,
+        Self::Limit,
+        Self::PostOnly,
+        Self::StopLossLimit,
+        Self::TakeProfitLimit,
+        Self::Oco,
+    ];
+
+    fn has_price(self) -> bool {
+        self != Self::Market
+    }
+
+    fn has_stop_price(self) -> bool {
+        matches!(
+            self,
+            Self::StopLossLimit | Self::TakeProfitLimit | Self::Oco
+        )
+    }
+
+    fn has_stop_limit_price(self) -> bool {
+        self == Self::Oco
+    }
+
+    /// OCO applies time in force to its stop limit leg
+    fn has_time_in_force(self) -> bool {
+        !matches!(self, Self::Market | Self::PostOnly)
+    }
+
+    fn order_type(self) -> OrderType {
+        match self {
+            Self::Market => OrderType::Market,
+            Self::Limit => OrderType::Limit,
+            Self::PostOnly | Self::Oco => OrderType::LimitMaker,
+            Self::StopLossLimit => OrderType::StopLossLimit,
+            Self::TakeProfitLimit => OrderType::TakeProfitLimit,
+        }
+    }
+}
+
+impl Display for OrderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            OrderKind::Market => "Market",
+            OrderKind::Limit => "Limit",
+            OrderKind::PostOnly => "Post only",
+            OrderKind::StopLossLimit => "Stop loss limit",
+            OrderKind::TakeProfitLimit => "Take profit limit",
+            OrderKind::Oco => "OCO",
+        };
+
+        f.write_str(name)
+    }
+}
+
+const TIME_IN_FORCE: [TimeInForce; 3] = [TimeInForce::Gtc, TimeInForce::Ioc, TimeInForce::Fok];
+
+fn market_error(message: String) -> Command<Message> {
+    Command::perform(async {}, move |_| {
+        Message::DispatchErr(("market".to_string(), message))
+    })
+}
+
+fn parse_field(name: &str, value: &str) -> Result<f64, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid {name}: {value:?}"))
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum MarketPanelMessage {
     BuyPressed,
     SellPressed,
+    OrderKindSelected(OrderKind),
+    TimeInForceSelected(TimeInForce),
     PriceMultiplied(f64),
     PriceInput(String),
+    StopPriceInput(String),
+    StopLimitPriceInput(String),
     AmountMultiplied(f64),
     AmountInput(String),
     PairSet,
@@ -41,17 +129,28 @@ pub(crate) enum MarketPanelMessage {
 }
 
 pub(crate) struct Market {
+    kind: OrderKind,
+    time_in_force: TimeInForce,
     price: String,
+    stop_price: String,
+    stop_limit_price: String,
     amount: String,
     pair: String,
+    /// Pair being typed, becomes `pair` once submitted
+    pair_input: String,
 }
 
 impl Market {
     pub(crate) fn new() -> Self {
         Self {
+            kind: OrderKind::Limit,
+            time_in_force: TimeInForce::Gtc,
             price: String::default(),
+            stop_price: String::default(),
+            stop_limit_price: String::default(),
             amount: String::default(),
             pair: "BTCUSDT".to_owned(),
+            pair_input: "BTCUSDT".to_owned(),
         }
     }
 
@@ -61,28 +160,68 @@ impl Market {
     }
 
     pub(crate) fn view(&self) -> Element<'_, MarketPanelMessage> {
+        let kind = self.kind;
+
+        let price_input = kind.has_price().then(|| {
+            column![
+                tin!("price", &self.price)
+                    .on_input(MarketPanelMessage::PriceInput)
+                    .width(150.0),
+                row![
+                    bbtn!(text("-0.1%").size(12))
+                        .on_press(MarketPanelMessage::PriceMultiplied(-0.1)),
+                    bbtn!(text("+0.1%").size(12))
+                        .on_press(MarketPanelMessage::PriceMultiplied(0.1)),
+                ]
+                .spacing(2.0)
+                .width(150.0),
+            ]
+        });
+
+        let stop_inputs = row![]
+            .push_maybe(kind.has_stop_price().then(|| {
+                tin!("stop", &self.stop_price)
+                    .on_input(MarketPanelMessage::StopPriceInput)
+                    .width(Length::Fill)
+            }))
+            .push_maybe(kind.has_stop_limit_price().then(|| {
+                tin!("stop limit", &self.stop_limit_price)
+                    .on_input(MarketPanelMessage::StopLimitPriceInput)
+                    .width(Length::Fill)
+            }))
+            .spacing(4.0)
+            .width(300.0);
+
+        let type_selectors = row![pick_list(
+            OrderKind::ALL.as_slice(),
+            Some(kind),
+            MarketPanelMessage::OrderKindSelected
+        )
+        .text_size(12)
+        .width(Length::Fill)]
+        .push_maybe(kind.has_time_in_force().then(|| {
+            pick_list(
+                TIME_IN_FORCE.as_slice(),
+                Some(self.time_in_force),
+                MarketPanelMessage::TimeInForceSelected,
+            )
+            .text_size(12)
+        }))
+        .spacing(4.0)
+        .width(300.0);
+
         container(
             column![
                 Space::new(Length::Fill, 1.0),
-                tin!("type a pair", &self.pair)
+                tin!("type a pair", &self.pair_input)
                     .on_input(MarketPanelMessage::PairInput)
                     .width(300.0)
                     .on_submit(MarketPanelMessage::PairSet),
-                row![
-                    column![
-                        tin!("price", &self.price)
-                            .on_input(MarketPanelMessage::PriceInput)
-                            .width(150.0),
-                        row![
-                            bbtn!(text("-0.1%").size(12))
-                                .on_press(MarketPanelMessage::PriceMultiplied(-0.1)),
-                            bbtn!(text("+0.1%").size(12))
-                                .on_press(MarketPanelMessage::PriceMultiplied(0.1)),
-                        ]
-                        .spacing(2.0)
-                        .width(150.0),
-                    ],
-                    column![
+                type_selectors,
+                stop_inputs,
+                row![]
+                    .push_maybe(price_input)
+                    .push(column![
                         tin!("amount", &self.amount)
                             .on_input(MarketPanelMessage::AmountInput)
                             .width(150.0),
@@ -100,10 +239,9 @@ impl Market {
                                 .on_press(MarketPanelMessage::AmountMultiplied(1.0)),
                         ]
                         .width(150.0),
-                    ]
-                ]
-                .spacing(4.0)
-                .width(300.0),
+                    ])
+                    .spacing(4.0)
+                    .width(300.0),
                 row![
                     button(tb("Buy").style(iced::Color::WHITE).size(12))
                         .style(iced::theme::Button::Custom(Box::new(GreenBtn {})))
@@ -130,71 +268,156 @@ impl Market {
         msg: MarketPanelMessage,
         api: &Client,
         data: &AppData,
-        ws: &Websockets,
     ) -> Command<Message> {
         match msg {
-            MarketPanelMessage::BuyPressed => self.buy(api),
-            MarketPanelMessage::SellPressed => self.sell(api),
+            MarketPanelMessage::BuyPressed => self.submit(api, data, OrderSide::Buy),
+            MarketPanelMessage::SellPressed => self.submit(api, data, OrderSide::Sell),
+            MarketPanelMessage::OrderKindSelected(kind) => {
+                self.kind = kind;
+                Command::none()
+            }
+            MarketPanelMessage::TimeInForceSelected(time_in_force) => {
+                self.time_in_force = time_in_force;
+                Command::none()
+            }
             MarketPanelMessage::AmountMultiplied(f) => {
-                let usdt_b = data
+                let Some(info) = data.symbols.get(&self.pair) else {
+                    return market_error(format!("unknown market {}", self.pair));
+                };
+                let quote_free = data
                     .balances
                     .iter()
-                    .find(|b| b.asset == "USDT")
-                    .unwrap()
-                    .free;
-                self.amount = (usdt_b * f).to_string();
+                    .find(|b| b.asset == info.quote)
+                    .map_or(0.0, |b| b.free);
+                let price = self
+                    .price
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|p| *p > 0.0)
+                    .unwrap_or(data.prices.price(&self.pair) as f64);
+
+                if price > 0.0 {
+                    self.amount = info.round_qty(quote_free * f / price).to_string();
+                }
                 Command::none()
             }
             MarketPanelMessage::PriceInput(new) => {
                 self.price = new;
                 Command::none()
             }
+            MarketPanelMessage::StopPriceInput(new) => {
+                self.stop_price = new;
+                Command::none()
+            }
+            MarketPanelMessage::StopLimitPriceInput(new) => {
+                self.stop_limit_price = new;
+                Command::none()
+            }
             MarketPanelMessage::AmountInput(new) => {
                 self.amount = new;
                 Command::none()
             }
             MarketPanelMessage::PriceMultiplied(inc) => {
-                let price = data.prices.price(&self.pair);
-                self.price =
-                    (((price as f64 * (1.0 + (inc / 100.0))) * 100.0).round() / 100.0).to_string();
-                Command::none()
-            }
-            MarketPanelMessage::PairSet => {
-                ws.track_new_currency_pair(&self.pair);
+                let price = data.prices.price(&self.pair) as f64 * (1.0 + (inc / 100.0));
+                self.price = match data.symbols.get(&self.pair) {
+                    Some(info) => info.round_price(price),
+                    None => (price * 100.0).round() / 100.0,
+                }
+                .to_string();
                 Command::none()
             }
+            MarketPanelMessage::PairSet => match data.symbols.resolve(&self.pair_input, "USDT") {
+                Some(info) => {
+                    self.set_currency_pair(info.symbol.clone());
+                    Command::none()
+                }
+                None => market_error(format!("unknown market {}", self.pair_input)),
+            },
             MarketPanelMessage::PairInput(new) => {
-                self.pair = new.to_uppercase();
+                self.pair_input = new.to_uppercase();
                 Command::none()
             }
         }
     }
 
-    fn buy(&mut self, api: &Client) -> Command<Message> {
-        api.trade_spot(
-            self.pair.clone(),
-            self.price.parse().unwrap(),
-            self.amount.parse().unwrap(),
-            binance::rest_model::OrderSide::Buy,
-        )
+    fn submit(&mut self, api: &Client, data: &AppData, side: OrderSide) -> Command<Message> {
+        self.order(api, data, side).unwrap_or_else(market_error)
     }
 
-    fn sell(&mut self, api: &Client) -> Command<Message> {
-        api.trade_spot(
-            self.pair.clone(),
-            self.price.parse().unwrap(),
-            self.amount.parse().unwrap(),
-            binance::rest_model::OrderSide::Sell,
-        )
-    }
+    /// Build order from inputs relevant to selected order type
+    ///
+    /// Prices and quantity are rounded to market filters and written back to inputs, so what is
+    /// shown is what gets sent.
+    fn order(
+        &mut self,
+        api: &Client,
+        data: &AppData,
+        side: OrderSide,
+    ) -> Result<Command<Message>, String> {
+        let kind = self.kind;
+        let info = data
+            .symbols
+            .get(&self.pair)
+            .ok_or_else(|| format!("unknown market {}", self.pair))?;
+
+        let qty = info.round_qty(parse_field("amount", &self.amount)?);
+        self.amount = qty.to_string();
+
+        let price = if kind.has_price() {
+            let price = info.round_price(parse_field("price", &self.price)?);
+            self.price = price.to_string();
+            Some(price)
+        } else {
+            None
+        };
+        let stop_price = if kind.has_stop_price() {
+            let stop_price = info.round_price(parse_field("stop price", &self.stop_price)?);
+            self.stop_price = stop_price.to_string();
+            info.validate(Some(stop_price), qty)?;
+            Some(stop_price)
+        } else {
+            None
+        };
+
+        // market orders are checked against last traded price
+        let reference_price = price.or_else(|| {
+            let last = data.prices.price(&self.pair) as f64;
+            (last > 0.0).then_some(last)
+        });
+        info.validate(reference_price, qty)?;
 
-    // FIXME: this is totally wrong and broken
-    /// Set new pair from selected currency
-    pub(crate) fn set_currency_pair(&mut self, mut new: String) {
-        // FIXME: this breaks with any other currency
-        if !(new.ends_with("USDT") || new.ends_with("BTC") || new.ends_with("ETH")) {
-            new = format!("{new}USDT");
+        if kind == OrderKind::Oco {
+            let stop_limit_price =
+                info.round_price(parse_field("stop limit price", &self.stop_limit_price)?);
+            self.stop_limit_price = stop_limit_price.to_string();
+            info.validate(Some(stop_limit_price), qty)?;
+
+            return Ok(api.trade_spot_oco(OcoRequest {
+                symbol: info.symbol.clone(),
+                side,
+                qty,
+                price: price.unwrap_or_default(),
+                stop_price: stop_price.unwrap_or_default(),
+                stop_limit_price,
+                stop_limit_time_in_force: self.time_in_force,
+            }));
         }
+
+        Ok(api.trade_spot(OrderRequest {
+            symbol: info.symbol.clone(),
+            side,
+            order_type: kind.order_type(),
+            time_in_force: kind.has_time_in_force().then_some(self.time_in_force),
+            price,
+            stop_price,
+            qty,
+        }))
+    }
+
+    /// Set new pair resolved from exchange info
+    pub(crate) fn set_currency_pair(&mut self, new: String) {
+        self.pair_input.clone_from(&new);
         self.pair = new;
     }
 }
@@ -2,31 +2,14 @@ pub(crate) mod balances;
 pub(crate) mod book;
 pub(crate) mod calculator;
 pub(crate) mod chart;
+pub(crate) mod depth;
+pub(crate) mod log;
 pub(crate) mod market;
+pub(crate) mod open_orders;
 pub(crate) mod orders;
 pub(crate) mod trades;
 pub(crate) mod watchlist;
 
-/* pub fn handle_hotkey(key_code: keyboard::KeyCode) -> Option<Message> {
-    use keyboard::KeyCode;
-    use pane_grid::{Axis, Direction};
-
-    let direction = match key_code {
-        KeyCode::Up => Some(Direction::Up),
-        KeyCode::Down => Some(Direction::Down),
-        KeyCode::Left => Some(Direction::Left),
-        KeyCode::Right => Some(Direction::Right),
-        _ => None,
-    };
-
-    match key_code {
-        KeyCode::V => Some(Message::SplitFocused(Axis::Vertical)),
-        KeyCode::H => Some(Message::SplitFocused(Axis::Horizontal)),
-        KeyCode::W => Some(Message::CloseFocused),
-        _ => direction.map(Message::FocusAdjacent),
-    }
-} */
-
 pub mod style {
     use iced::widget::container;
     use iced::{Color, Theme};
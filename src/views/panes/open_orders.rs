@@ -0,0 +1,126 @@
+use super::orders::{t, tb};
+
+use crate::{
+    api::Client,
+    data::AppData,
+    exchange::OrderSide,
+    message::Message,
+    theme::h2c,
+    views::{
+        components::better_btn::{BetterBtn, RedBtn},
+        dashboard::DashboardMessage,
+    },
+};
+
+use iced::{
+    widget::{button, column, container, row, scrollable, text, Column, Space},
+    Alignment, Command, Element, Length,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) enum OpenOrdersMessage {
+    Cancel { symbol: String, order_id: u64 },
+    CancelAll(String),
+}
+
+pub(crate) struct OpenOrdersPane {}
+
+impl OpenOrdersPane {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+
+    pub(crate) fn update(&mut self, msg: OpenOrdersMessage, api: &Client) -> Command<Message> {
+        match msg {
+            OpenOrdersMessage::Cancel { symbol, order_id } => api.cancel_order(symbol, order_id),
+            OpenOrdersMessage::CancelAll(symbol) => api.cancel_all_orders(symbol),
+        }
+    }
+
+    /// `pair` is market currently selected in dashboard, it can be cleared in one click
+    pub(crate) fn view<'a>(
+        &'a self,
+        data: &'a AppData,
+        pair: &str,
+    ) -> Element<'a, DashboardMessage> {
+        let os = &data.open_orders;
+
+        if os.is_empty() {
+            return container(tb("No open orders"))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let cancel_all = if os.iter().any(|o| o.symbol == pair) {
+            Some(
+                button(text(format!("Cancel all {pair}")).size(12))
+                    .style(iced::theme::Button::Custom(Box::new(BetterBtn {})))
+                    .padding(4)
+                    .on_press(OpenOrdersMessage::CancelAll(pair.to_owned()).into()),
+            )
+        } else {
+            None
+        };
+
+        let header = row![
+            tb("Symbol").width(Length::Fixed(100.0)),
+            tb("Side").width(Length::Fixed(60.0)),
+            tb("Type").width(Length::Fixed(120.0)),
+            tb("Price").width(Length::Fixed(100.0)),
+            tb("Filled").width(Length::Fixed(150.0)),
+            tb("Status").width(Length::Fixed(130.0)),
+            tb("Time").width(Length::Fixed(110.0)),
+            Space::new(Length::Fill, 0.0),
+        ]
+        .push_maybe(cancel_all)
+        .align_items(Alignment::Center)
+        .padding([0, 12]);
+
+        let rows = os.iter().map(|o| {
+            let time = chrono::DateTime::from_timestamp_millis(o.time as i64)
+                .map(|dt| dt.format("%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            let side_color = if o.side == OrderSide::Buy {
+                h2c("11EE11")
+            } else {
+                h2c("EE1111")
+            }
+            .unwrap();
+
+            container(
+                row![
+                    tb(&o.symbol)
+                        .style(h2c("11EE11").unwrap())
+                        .width(Length::Fixed(100.0)),
+                    t(format!("{:?}", o.side))
+                        .style(side_color)
+                        .width(Length::Fixed(60.0)),
+                    t(format!("{:?}", o.order_type)).width(Length::Fixed(120.0)),
+                    t(format!("{:.3}", o.price)).width(Length::Fixed(100.0)),
+                    t(format!("{} / {}", o.executed_qty, o.orig_qty)).width(Length::Fixed(150.0)),
+                    t(format!("{:?}", o.status)).width(Length::Fixed(130.0)),
+                    t(time).width(Length::Fixed(110.0)),
+                    Space::new(Length::Fill, 0.0),
+                    button(text("Cancel").size(12))
+                        .style(iced::theme::Button::Custom(Box::new(RedBtn {})))
+                        .padding(4)
+                        .on_press(
+                            OpenOrdersMessage::Cancel {
+                                symbol: o.symbol.clone(),
+                                order_id: o.order_id,
+                            }
+                            .into()
+                        ),
+                ]
+                .align_items(Alignment::Center),
+            )
+            .padding([2, 4])
+            .into()
+        });
+
+        column![header, scrollable(Column::with_children(rows).padding(8))].into()
+    }
+}
@@ -1,11 +1,10 @@
 use crate::{
-    api::Client,
     data::AppData,
+    exchange::{OrderSide, OrderType},
     theme::h2c,
     views::{components::loading::loader, dashboard::DashboardMessage},
 };
 
-use binance::rest_model::{OrderSide, OrderType};
 use iced::{
     widget::{column, container, row, text, Column, Space},
     Element, Font, Length,
@@ -73,9 +72,12 @@ impl OrdersPane {
                 tb(s).style(h2c("11EE11").unwrap())
             }
             .width(Length::Fixed(100.0));
-            let [base, quote] = Client::split_symbol(&b.symbol).unwrap();
+            let [base, quote] = data
+                .symbols
+                .split(&b.symbol)
+                .unwrap_or([b.symbol.as_str(), ""]);
             let norm_price = if b.order_type == OrderType::Market {
-                b.cummulative_quote_qty / b.executed_qty
+                b.cumulative_quote_qty / b.executed_qty
             } else {
                 b.price
             };
@@ -98,12 +100,8 @@ impl OrdersPane {
 
             let pnl = {
                 let pnl_value = match b.side {
-                    binance::rest_model::OrderSide::Buy => {
-                        b.executed_qty * (price_now as f64 - norm_price)
-                    }
-                    binance::rest_model::OrderSide::Sell => {
-                        b.executed_qty * (norm_price - price_now as f64)
-                    }
+                    OrderSide::Buy => b.executed_qty * (price_now as f64 - norm_price),
+                    OrderSide::Sell => b.executed_qty * (norm_price - price_now as f64),
                 };
                 t(format!("{pnl_value:.0}$"))
                     .width(Length::Fixed(100.0))
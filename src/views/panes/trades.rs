@@ -17,10 +17,10 @@ impl TradesPane {
     pub(crate) fn new() -> Self {
         Self {}
     }
-    pub fn view<'a>(&'a self, data: &'a AppData) -> Element<'a, DashboardMessage> {
-        if data.trades.is_empty() {
+    pub fn view<'a>(&'a self, data: &'a AppData, symbol: &str) -> Element<'a, DashboardMessage> {
+        let Some(trades) = data.trades.get(symbol).filter(|t| !t.is_empty()) else {
             return loader!().into();
-        }
+        };
 
         column![
             row![
@@ -29,7 +29,7 @@ impl TradesPane {
                 tb("Time").width(Length::Fill)
             ],
             scrollable(Column::with_children(
-                data.trades
+                trades
                     .iter()
                     .rev()
                     .map(|b| {
@@ -98,7 +98,6 @@ impl WatchlistPane {
                     data.prices
                         .sorted_and_filtered()
                         .map(|(n, p)| asset_button(n, *p))
-                        .map(Element::from)
                 )
                 .padding(8)
             )
@@ -1,7 +1,12 @@
-use crate::{config::Config, message::Message};
+use std::path::PathBuf;
+
+use crate::{
+    config::{Config, Workspace},
+    message::{MaybeError, Message},
+};
 
 use iced::{
-    widget::{button, column, container, row, text, text_input, Container},
+    widget::{button, column, container, row, text, text_input, Column, Container, Space},
     Color, Command, Length,
 };
 
@@ -9,6 +14,19 @@ use super::panes::orders::tb;
 
 pub(crate) struct SettingsView {
     new_config: Config,
+    /// Comma separated lists, parsed on save
+    history_symbols: String,
+    history_quotes: String,
+    workspace_name: String,
+    /// File used by workspace import/export
+    workspace_path: String,
+}
+
+fn parse_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect()
 }
 
 #[derive(Debug, Clone)]
@@ -19,23 +37,51 @@ pub(crate) enum SettingsMessage {
     /// on_input events
     ApiKeyInput(String),
     ApiSecretInput(String),
+    HistorySymbolsInput(String),
+    HistoryQuotesInput(String),
+    WorkspaceNameInput(String),
+    WorkspacePathInput(String),
+
+    /// Store current layout under entered name
+    WorkspaceSaved,
+    WorkspaceDeleted(String),
+    WorkspaceExported(String),
+    WorkspaceImport,
+    WorkspaceImported(Workspace),
 }
 
 impl SettingsView {
     pub(crate) fn new(config: Config) -> Self {
-        Self { new_config: config }
+        Self {
+            history_symbols: config.history_symbols.join(", "),
+            history_quotes: config.history_quotes.join(", "),
+            new_config: config,
+            workspace_name: String::new(),
+            workspace_path: String::new(),
+        }
+    }
+
+    /// Path input, or `<name>.json` in working directory when empty
+    fn workspace_path(&self, name: &str) -> PathBuf {
+        if self.workspace_path.trim().is_empty() {
+            PathBuf::from(format!("{name}.json"))
+        } else {
+            PathBuf::from(self.workspace_path.trim())
+        }
     }
 
     pub(crate) fn update(&mut self, message: SettingsMessage) -> Command<Message> {
         match message {
             SettingsMessage::SaveConfig => {
+                self.new_config.history_symbols = parse_list(&self.history_symbols);
+                self.new_config.history_quotes = parse_list(&self.history_quotes);
                 let new_config = self.new_config.clone();
 
                 Command::perform(
                     async {
                         new_config.save().map_err(|err| err.to_string())?;
 
-                        Ok(new_config)
+                        Ok(Box::new(new_config))
                     },
                     Message::ConfigUpdated,
                 )
@@ -48,6 +94,79 @@ impl SettingsView {
                 self.new_config.api_secret_key = value;
                 Command::none()
             }
+            SettingsMessage::HistorySymbolsInput(value) => {
+                self.history_symbols = value;
+                Command::none()
+            }
+            SettingsMessage::HistoryQuotesInput(value) => {
+                self.history_quotes = value;
+                Command::none()
+            }
+            SettingsMessage::WorkspaceNameInput(value) => {
+                self.workspace_name = value;
+                Command::none()
+            }
+            SettingsMessage::WorkspacePathInput(value) => {
+                self.workspace_path = value;
+                Command::none()
+            }
+            SettingsMessage::WorkspaceSaved => {
+                let name = self.workspace_name.trim().to_owned();
+                if name.is_empty() {
+                    return Command::none();
+                }
+
+                let layout = self.new_config.layout.clone().unwrap_or_default();
+                self.new_config.upsert_workspace(Workspace {
+                    name: name.clone(),
+                    layout,
+                });
+                self.new_config.workspace = Some(name);
+                self.workspace_name.clear();
+                Command::none()
+            }
+            SettingsMessage::WorkspaceDeleted(name) => {
+                self.new_config.workspaces.retain(|w| w.name != name);
+                if self.new_config.workspace.as_ref() == Some(&name) {
+                    self.new_config.workspace = None;
+                }
+                Command::none()
+            }
+            SettingsMessage::WorkspaceExported(name) => {
+                let Some(workspace) = self
+                    .new_config
+                    .workspaces
+                    .iter()
+                    .find(|w| w.name == name)
+                    .cloned()
+                else {
+                    return Command::none();
+                };
+                let path = self.workspace_path(&name);
+
+                Command::perform(async move { workspace.export(&path) }, |result| {
+                    MaybeError::new("workspace export".to_string())
+                        .maybe(&result)
+                        .into()
+                })
+            }
+            SettingsMessage::WorkspaceImport => {
+                let path = self.workspace_path("workspace");
+
+                Command::perform(
+                    async move { Workspace::import(&path) },
+                    |result| match result {
+                        Ok(workspace) => SettingsMessage::WorkspaceImported(workspace).into(),
+                        Err(e) => {
+                            Message::DispatchErr(("workspace import".to_string(), e.to_string()))
+                        }
+                    },
+                )
+            }
+            SettingsMessage::WorkspaceImported(workspace) => {
+                self.new_config.upsert_workspace(workspace);
+                Command::none()
+            }
         }
     }
 
@@ -60,6 +179,26 @@ impl SettingsView {
             .secure(true)
             .width(Length::Fill)
             .on_input(|s| Message::Settings(SettingsMessage::ApiSecretInput(s)));
+        let history_symbols_input = text_input("LINKUSDT, ETHBTC", &self.history_symbols)
+            .width(Length::Fill)
+            .on_input(|s| Message::Settings(SettingsMessage::HistorySymbolsInput(s)));
+        let history_quotes_input = text_input("USDT, BTC", &self.history_quotes)
+            .width(Length::Fill)
+            .on_input(|s| Message::Settings(SettingsMessage::HistoryQuotesInput(s)));
+
+        let workspaces = Column::with_children(self.new_config.workspaces.iter().map(|w| {
+            row![
+                text(&w.name).size(14).width(Length::Fill),
+                button(tb("Export"))
+                    .on_press(SettingsMessage::WorkspaceExported(w.name.clone()).into()),
+                button(tb("Delete"))
+                    .on_press(SettingsMessage::WorkspaceDeleted(w.name.clone()).into()),
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+            .into()
+        }))
+        .spacing(6);
 
         container(
             column![
@@ -69,6 +208,35 @@ impl SettingsView {
                     api_secret_key_input,
                 ]
                 .spacing(10),
+                row![
+                    text("History markets:").width(Length::Fixed(100.0)),
+                    history_symbols_input,
+                ]
+                .spacing(10),
+                row![
+                    text("History quotes:").width(Length::Fixed(100.0)),
+                    history_quotes_input,
+                ]
+                .spacing(10),
+                row![
+                    text("Workspaces:").width(Length::Fixed(100.0)),
+                    text_input("Name", &self.workspace_name)
+                        .width(Length::Fill)
+                        .on_input(|s| SettingsMessage::WorkspaceNameInput(s).into())
+                        .on_submit(SettingsMessage::WorkspaceSaved.into()),
+                    button(tb("Save current layout as"))
+                        .on_press(SettingsMessage::WorkspaceSaved.into()),
+                ]
+                .spacing(10),
+                row![Space::new(Length::Fixed(100.0), 1), workspaces].spacing(10),
+                row![
+                    text("Workspace file:").width(Length::Fixed(100.0)),
+                    text_input("workspace.json", &self.workspace_path)
+                        .width(Length::Fill)
+                        .on_input(|s| SettingsMessage::WorkspacePathInput(s).into()),
+                    button(tb("Import")).on_press(SettingsMessage::WorkspaceImport.into()),
+                ]
+                .spacing(10),
                 button(tb("Save")).on_press(SettingsMessage::SaveConfig.into()),
             ]
             .spacing(10)
@@ -1,100 +1,351 @@
-use std::{error::Error, sync::atomic::AtomicBool};
+use std::{
+    collections::VecDeque, error::Error, future::Future, sync::atomic::AtomicBool, time::Instant,
+};
 
-use binance::websockets::diff_book_depth_stream;
-use iced::subscription::{self, Subscription};
-use std::collections::BTreeMap;
+use binance::{
+    api::Binance,
+    market::Market,
+    rest_model::{Asks, Bids, OrderBook as DepthSnapshot},
+    ws_model::DepthOrderBookEvent,
+};
+use tracing::{info, warn};
 
-use super::{WsEvent, WsListener, WsMessage};
+use super::{Reconnect, Stream, WsEvent, WsListener, WsMessage};
+use crate::data::OrderBook;
 
-#[derive(Debug, Clone)]
-pub(crate) struct OrderBookDetails {
-    pub(crate) sym: String,
-    pub(crate) bids: BTreeMap<String, f64>,
-    pub(crate) asks: BTreeMap<String, f64>,
+/// Depth of REST snapshot used to seed local book
+const SNAPSHOT_DEPTH: u16 = 1000;
+
+/// Diffs kept while snapshot loads, oldest are dropped first
+const MAX_BUFFERED: usize = 1000;
+
+/// Result of applying depth diff to local book
+#[derive(Debug, PartialEq)]
+enum Sequence {
+    /// Diff is older than book, nothing changed
+    Stale,
+    /// Diff applied
+    Applied,
+    /// Diff does not continue book, book must be rebuilt from snapshot
+    Gap,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) enum Message {
-    NewPair(String),
+/// Local order book replica
+///
+/// Seeded from REST snapshot and kept up to date with depth diffs, sequenced by update id.
+#[derive(Debug, Default)]
+struct LocalBook {
+    details: OrderBook,
+    /// Update id of last applied snapshot or diff, `None` until snapshot is loaded
+    last_update_id: Option<u64>,
+    /// Whether any diff was applied since snapshot
+    ///
+    /// First diff after snapshot may overlap it, later ones must follow each other exactly
+    continued: bool,
+}
+
+impl LocalBook {
+    fn is_synced(&self) -> bool {
+        self.last_update_id.is_some()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn load_snapshot(&mut self, symbol: String, snapshot: DepthSnapshot) {
+        let DepthSnapshot {
+            last_update_id,
+            bids,
+            asks,
+        } = snapshot;
+
+        self.details = OrderBook::new(symbol);
+        self.update_levels(&bids, &asks);
+        self.last_update_id = Some(last_update_id);
+        self.continued = false;
+    }
+
+    fn apply(&mut self, event: &DepthOrderBookEvent) -> Sequence {
+        let Some(last_update_id) = self.last_update_id else {
+            return Sequence::Gap;
+        };
+
+        if event.final_update_id <= last_update_id {
+            return Sequence::Stale;
+        }
+
+        let expected = last_update_id + 1;
+        let continues = if self.continued {
+            event.first_update_id == expected
+        } else {
+            event.first_update_id <= expected
+        };
+
+        if !continues {
+            self.reset();
+            return Sequence::Gap;
+        }
+
+        self.details.sym.clone_from(&event.symbol);
+        self.update_levels(&event.bids, &event.asks);
+        self.last_update_id = Some(event.final_update_id);
+        self.continued = true;
+
+        Sequence::Applied
+    }
+
+    fn update_levels(&mut self, bids: &[Bids], asks: &[Asks]) {
+        for bid in bids {
+            self.details.update_bid(bid.price, bid.qty);
+        }
+        for ask in asks {
+            self.details.update_ask(ask.price, ask.qty);
+        }
+    }
 }
 
-#[derive(Debug)]
 pub(crate) struct BookWs {
+    /// Uppercase symbol
     pair: String,
+    market: Market,
+    book: LocalBook,
+    /// Diffs received while book is not synced, replayed over next snapshot
+    buffered: VecDeque<DepthOrderBookEvent>,
+    /// Delay between failed snapshot requests
+    reconnect: Reconnect,
+    /// Failed snapshot requests since last synced book
+    attempt: u32,
+    /// Next snapshot is not requested before this time
+    retry_at: Option<Instant>,
+    /// Whether snapshot request is in flight
+    loading: bool,
 }
 
 impl BookWs {
-    fn new(pair: String) -> Self {
-        Self { pair }
+    pub(crate) fn new(pair: String, reconnect: Reconnect) -> Self {
+        Self {
+            pair,
+            market: Binance::new(None, None),
+            book: LocalBook::default(),
+            buffered: VecDeque::new(),
+            reconnect,
+            attempt: 0,
+            retry_at: None,
+            loading: false,
+        }
+    }
+
+    fn buffer(&mut self, event: DepthOrderBookEvent) {
+        if self.buffered.len() == MAX_BUFFERED {
+            self.buffered.pop_front();
+        }
+        self.buffered.push_back(event);
+    }
+
+    /// Wait before next snapshot request, so failures do not hit rest api at stream rate
+    fn back_off(&mut self) {
+        self.retry_at = Some(Instant::now() + self.reconnect.delay(self.attempt));
+        self.attempt += 1;
+    }
+
+    /// REST snapshot request for unsynced book, run apart from stream so it keeps draining
+    ///
+    /// `None` while book is synced, request is in flight or retry delay has not passed.
+    pub(crate) fn snapshot_request(
+        &mut self,
+    ) -> Option<impl Future<Output = Result<DepthSnapshot, String>> + Send + 'static> {
+        if self.book.is_synced()
+            || self.loading
+            || self.retry_at.is_some_and(|at| Instant::now() < at)
+        {
+            return None;
+        }
+
+        self.loading = true;
+        let market = self.market.clone();
+        let pair = self.pair.clone();
+        Some(async move {
+            market
+                .get_custom_depth(pair, SNAPSHOT_DEPTH)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    /// Rebuild local book from result of `snapshot_request` and buffered diffs
+    ///
+    /// Returns book once it is synced.
+    pub(crate) fn snapshot_loaded(
+        &mut self,
+        result: Result<DepthSnapshot, String>,
+    ) -> Option<OrderBook> {
+        self.loading = false;
+        match result {
+            Ok(snapshot) => self.load(snapshot),
+            Err(e) => {
+                tracing::error!("book snapshot error: {e}");
+                self.back_off();
+                None
+            }
+        }
+    }
+
+    /// Seed book with snapshot and replay buffered diffs over it
+    fn load(&mut self, snapshot: DepthSnapshot) -> Option<OrderBook> {
+        info!(
+            "loaded {} book snapshot at {}",
+            self.pair, snapshot.last_update_id
+        );
+        self.book.load_snapshot(self.pair.clone(), snapshot);
+
+        while let Some(diff) = self.buffered.pop_front() {
+            if self.book.apply(&diff) == Sequence::Gap {
+                // snapshot is older than buffered diffs, take newer one later
+                self.buffered.push_front(diff);
+                self.back_off();
+                return None;
+            }
+        }
+
+        self.attempt = 0;
+        self.retry_at = None;
+        Some(self.book.details.clone())
     }
 }
 
 impl WsListener for BookWs {
-    type Event = binance::ws_model::DepthOrderBookEvent;
-    type Input = Message;
-    type Output = OrderBookDetails;
+    type Event = DepthOrderBookEvent;
+    type Input = ();
+    type Output = OrderBook;
+
+    fn stream(&self) -> Stream {
+        Stream::Book(self.pair.clone())
+    }
 
     fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage {
         WsMessage::Book(msg)
     }
 
-    async fn endpoint(&self) -> Result<String, Box<dyn Error + Send>> {
-        Ok(diff_book_depth_stream(&self.pair, 1000))
+    async fn endpoint(&mut self) -> Result<String, Box<dyn Error + Send>> {
+        // diffs every second
+        Ok(format!("{}@depth", self.pair.to_lowercase()))
     }
 
-    fn handle_event(&self, event: Self::Event) -> Self::Output {
-        let binance::ws_model::DepthOrderBookEvent {
-            event_time: _,
-            symbol,
-            first_update_id: _,
-            final_update_id: _,
-            bids,
-            asks,
-        } = event;
-        let mut b: BTreeMap<String, f64> = BTreeMap::new();
-        let mut a: BTreeMap<String, f64> = BTreeMap::new();
-
-        for bid in bids {
-            let price = bid.price;
-            let quantity = bid.qty;
-            if quantity == 0.0 {
-                b.remove(&price.to_string());
-            } else {
-                b.insert(price.to_string(), quantity);
+    async fn handle_event(&mut self, event: Self::Event) -> Option<Self::Output> {
+        if self.book.is_synced() {
+            match self.book.apply(&event) {
+                Sequence::Applied => return Some(self.book.details.clone()),
+                Sequence::Stale => return None,
+                Sequence::Gap => {
+                    warn!("{} book out of sequence, resyncing", self.pair);
+                    self.buffer(event);
+                    // empty book shows loader instead of outdated levels
+                    return Some(OrderBook::new(self.pair.clone()));
+                }
             }
         }
 
-        for ask in asks {
-            let price = ask.price;
-            let quantity = ask.qty;
-            if quantity == 0.0 {
-                a.remove(&price.to_string());
-            } else {
-                a.insert(price.to_string(), quantity);
-            }
-        }
+        // replayed once snapshot requested by mux arrives
+        self.buffer(event);
+        None
+    }
 
-        OrderBookDetails {
-            sym: symbol,
-            bids: b,
-            asks: a,
+    fn handle_input(&mut self, _: Self::Input, _: &mut AtomicBool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(
+        first_update_id: u64,
+        final_update_id: u64,
+        bids: &[(f64, f64)],
+    ) -> DepthOrderBookEvent {
+        DepthOrderBookEvent {
+            event_time: 0,
+            symbol: "BTCUSDT".to_owned(),
+            first_update_id,
+            final_update_id,
+            bids: bids
+                .iter()
+                .map(|&(price, qty)| Bids { price, qty })
+                .collect(),
+            asks: Vec::new(),
         }
     }
 
-    fn handle_input(&mut self, input: Self::Input, keep_running: &mut AtomicBool) {
-        match input {
-            Message::NewPair(new_pair) => {
-                self.pair = new_pair;
-                keep_running.store(false, std::sync::atomic::Ordering::Relaxed);
-            }
+    fn synced_book() -> LocalBook {
+        let mut book = LocalBook::default();
+        book.load_snapshot(
+            "BTCUSDT".to_owned(),
+            DepthSnapshot {
+                last_update_id: 100,
+                bids: vec![Bids {
+                    price: 10.0,
+                    qty: 1.0,
+                }],
+                asks: Vec::new(),
+            },
+        );
+        book
+    }
+
+    #[test]
+    fn applies_diffs_in_sequence() {
+        let mut book = synced_book();
+
+        assert_eq!(book.apply(&diff(90, 100, &[(11.0, 1.0)])), Sequence::Stale);
+        assert_eq!(
+            book.apply(&diff(95, 105, &[(10.0, 0.0)])),
+            Sequence::Applied
+        );
+        assert_eq!(
+            book.apply(&diff(106, 110, &[(12.0, 2.0)])),
+            Sequence::Applied
+        );
+
+        assert_eq!(book.details.best_bid(), Some((12.0, 2.0)));
+        assert_eq!(book.details.bids().count(), 1);
+        assert_eq!(book.last_update_id, Some(110));
+    }
+
+    #[test]
+    fn replays_buffered_diffs_over_snapshot() {
+        let snapshot = || DepthSnapshot {
+            last_update_id: 100,
+            bids: Vec::new(),
+            asks: Vec::new(),
         };
+
+        let mut ws = BookWs::new("BTCUSDT".to_owned(), Reconnect::default());
+        ws.buffer(diff(90, 99, &[(9.0, 1.0)]));
+        ws.buffer(diff(100, 102, &[(10.0, 1.0)]));
+        ws.buffer(diff(103, 104, &[(11.0, 1.0)]));
+        let book = ws.load(snapshot()).unwrap();
+        assert_eq!(book.best_bid(), Some((11.0, 1.0)));
+        assert_eq!(book.bids().count(), 2);
+        assert!(ws.buffered.is_empty());
+
+        // snapshot older than buffered diffs is retried later
+        let mut ws = BookWs::new("BTCUSDT".to_owned(), Reconnect::default());
+        ws.buffer(diff(110, 112, &[]));
+        assert!(ws.load(snapshot()).is_none());
+        assert_eq!(ws.buffered.len(), 1);
+        assert_eq!(ws.attempt, 1);
+        assert!(ws.retry_at.is_some());
     }
-}
 
-pub fn connect(pair: String) -> Subscription<WsMessage> {
-    struct Connect;
+    #[test]
+    fn detects_gaps() {
+        let mut book = synced_book();
+        assert_eq!(book.apply(&diff(102, 105, &[])), Sequence::Gap);
+        assert!(!book.is_synced());
 
-    subscription::channel(std::any::TypeId::of::<Connect>(), 100, |output| async {
-        BookWs::new(pair).run(output).await
-    })
+        let mut book = synced_book();
+        assert_eq!(book.apply(&diff(101, 105, &[])), Sequence::Applied);
+        assert_eq!(book.apply(&diff(107, 110, &[])), Sequence::Gap);
+        assert!(!book.is_synced());
+    }
 }
@@ -0,0 +1,276 @@
+//! Reconnect policy and liveness of websocket streams
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::ChartKey;
+
+/// Window over which message rate is measured
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+/// Websocket reconnect policy, stored in config
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct Reconnect {
+    /// Delay before first retry
+    pub(crate) initial_ms: u64,
+    pub(crate) max_ms: u64,
+    /// Growth of delay with every failed attempt
+    pub(crate) factor: f64,
+    /// Part of delay that is randomized, so streams do not reconnect all at once
+    pub(crate) jitter: f64,
+    /// Market stream without data for this long is resubscribed
+    pub(crate) stale_secs: u64,
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self {
+            initial_ms: 500,
+            max_ms: 60_000,
+            factor: 2.0,
+            jitter: 0.3,
+            stale_secs: 30,
+        }
+    }
+}
+
+impl Reconnect {
+    /// Delay before retry number `attempt`, counted from zero
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        self.delay_with(attempt, random_fraction())
+    }
+
+    /// `random` in `0..1` picks point inside jitter range
+    fn delay_with(&self, attempt: u32, random: f64) -> Duration {
+        let exponential = self.initial_ms as f64 * self.factor.max(1.0).powi(attempt as i32);
+        let capped = exponential.min(self.max_ms as f64);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+
+        Duration::from_millis((capped * (1.0 - jitter * random)) as u64)
+    }
+
+    pub(crate) fn stale_after(&self) -> Duration {
+        Duration::from_secs(self.stale_secs.max(1))
+    }
+}
+
+/// Random number in `0..1`, good enough to spread retries
+fn random_fraction() -> f64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Identity of one websocket feed
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Stream {
+    Prices,
+    User,
+    Trades(String),
+    Book(String),
+    Klines(ChartKey),
+}
+
+impl Stream {
+    /// Feed only sends on activity, so silence does not make it stale
+    pub(crate) fn is_event_driven(&self) -> bool {
+        matches!(self, Self::User | Self::Trades(_))
+    }
+}
+
+impl Display for Stream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Prices => write!(f, "prices"),
+            Self::User => write!(f, "user"),
+            Self::Trades(symbol) => write!(f, "{symbol} trades"),
+            Self::Book(symbol) => write!(f, "{symbol} book"),
+            Self::Klines(key) => write!(f, "{} {} klines", key.symbol, key.timeframe),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FeedStatus {
+    /// Not connected yet
+    Connecting,
+    Live,
+    /// Connected, but no data for too long
+    Stale,
+    /// Lost connection, retrying
+    Down,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct StreamHealth {
+    connected_since: Option<Instant>,
+    last_message: Option<Instant>,
+    /// Successful connections after the first one
+    reconnects: u32,
+    connections: u32,
+    /// Arrival of messages within rate window
+    recent: VecDeque<Instant>,
+}
+
+impl StreamHealth {
+    fn connected(&mut self, now: Instant) {
+        if self.connections > 0 {
+            self.reconnects += 1;
+        }
+        self.connections += 1;
+        self.connected_since = Some(now);
+    }
+
+    fn message(&mut self, now: Instant) {
+        self.last_message = Some(now);
+        self.recent.push_back(now);
+        while self
+            .recent
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > RATE_WINDOW)
+        {
+            self.recent.pop_front();
+        }
+    }
+
+    /// Messages per second
+    fn rate(&self, now: Instant) -> f64 {
+        let recent = self
+            .recent
+            .iter()
+            .filter(|t| now.duration_since(**t) <= RATE_WINDOW)
+            .count();
+        recent as f64 / RATE_WINDOW.as_secs_f64()
+    }
+}
+
+/// Health of all streams, kept in app data so panes can show it
+#[derive(Debug)]
+pub(crate) struct Streams {
+    health: BTreeMap<Stream, StreamHealth>,
+    stale_after: Duration,
+}
+
+impl Default for Streams {
+    fn default() -> Self {
+        Self {
+            health: BTreeMap::new(),
+            stale_after: Reconnect::default().stale_after(),
+        }
+    }
+}
+
+impl Streams {
+    pub(crate) fn set_stale_after(&mut self, stale_after: Duration) {
+        self.stale_after = stale_after;
+    }
+
+    pub(crate) fn connected(&mut self, stream: Stream) {
+        self.health
+            .entry(stream)
+            .or_default()
+            .connected(Instant::now());
+    }
+
+    pub(crate) fn disconnected(&mut self, stream: Stream) {
+        if let Some(health) = self.health.get_mut(&stream) {
+            health.connected_since = None;
+        }
+    }
+
+    pub(crate) fn message(&mut self, stream: Stream) {
+        self.health
+            .entry(stream)
+            .or_default()
+            .message(Instant::now());
+    }
+
+    pub(crate) fn status(&self, stream: &Stream) -> FeedStatus {
+        self.status_at(stream, Instant::now())
+    }
+
+    fn status_at(&self, stream: &Stream, now: Instant) -> FeedStatus {
+        let Some(health) = self.health.get(stream) else {
+            return FeedStatus::Connecting;
+        };
+        let Some(since) = health.connected_since else {
+            return match health.connections {
+                0 => FeedStatus::Connecting,
+                _ => FeedStatus::Down,
+            };
+        };
+
+        let last = health.last_message.unwrap_or(since).max(since);
+        if !stream.is_event_driven() && now.duration_since(last) > self.stale_after {
+            FeedStatus::Stale
+        } else {
+            FeedStatus::Live
+        }
+    }
+
+    /// One line description of stream state
+    pub(crate) fn summary(&self, stream: &Stream) -> String {
+        let now = Instant::now();
+        let status = self.status_at(stream, now);
+        let Some(health) = self.health.get(stream) else {
+            return format!("{stream}: {status:?}");
+        };
+
+        let ago = |t: Instant| format!("{}s ago", now.duration_since(t).as_secs());
+        let since = health.connected_since.map_or("-".to_owned(), ago);
+        let last = health.last_message.map_or("-".to_owned(), ago);
+
+        format!(
+            "{stream}: {status:?}, connected {since}, last message {last}, {:.1} msg/s, {} reconnects",
+            health.rate(now),
+            health.reconnects
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_and_streams_go_stale() {
+        let reconnect = Reconnect::default();
+        assert_eq!(reconnect.delay_with(0, 0.0), Duration::from_millis(500));
+        assert_eq!(reconnect.delay_with(3, 0.0), Duration::from_millis(4000));
+        assert_eq!(reconnect.delay_with(20, 0.0), Duration::from_secs(60));
+        assert_eq!(reconnect.delay_with(1, 1.0), Duration::from_millis(700));
+
+        let mut streams = Streams::default();
+        streams.set_stale_after(Duration::from_secs(5));
+        let book = Stream::Book("BTCUSDT".to_owned());
+        assert_eq!(streams.status(&book), FeedStatus::Connecting);
+
+        let start = Instant::now();
+        let health = streams.health.entry(book.clone()).or_default();
+        health.connected(start);
+        health.message(start);
+        assert_eq!(streams.status_at(&book, start), FeedStatus::Live);
+        assert_eq!(
+            streams.status_at(&book, start + Duration::from_secs(6)),
+            FeedStatus::Stale
+        );
+
+        streams.disconnected(book.clone());
+        assert_eq!(streams.status_at(&book, start), FeedStatus::Down);
+        streams.connected(book.clone());
+        assert_eq!(streams.health[&book].reconnects, 1);
+    }
+}
@@ -0,0 +1,45 @@
+use std::{error::Error, sync::atomic::AtomicBool};
+
+use binance::{websockets::kline_stream, ws_model::KlineEvent};
+
+use super::{Stream, WsEvent, WsListener, WsMessage};
+use crate::{data::ChartKey, exchange::Kline};
+
+/// Candles of one chart, current one is sent on every change
+#[derive(Debug)]
+pub(crate) struct KlinesWs {
+    key: ChartKey,
+}
+
+impl KlinesWs {
+    pub(crate) fn new(key: ChartKey) -> Self {
+        Self { key }
+    }
+}
+
+impl WsListener for KlinesWs {
+    type Event = KlineEvent;
+    type Input = ();
+    type Output = (ChartKey, Kline);
+
+    fn stream(&self) -> Stream {
+        Stream::Klines(self.key.clone())
+    }
+
+    fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage {
+        WsMessage::Kline(msg)
+    }
+
+    async fn endpoint(&mut self) -> Result<String, Box<dyn Error + Send>> {
+        Ok(kline_stream(
+            &self.key.symbol.to_lowercase(),
+            &self.key.timeframe,
+        ))
+    }
+
+    async fn handle_event(&mut self, event: Self::Event) -> Option<Self::Output> {
+        Some((self.key.clone(), event.kline.into()))
+    }
+
+    fn handle_input(&mut self, _: Self::Input, _: &mut AtomicBool) {}
+}
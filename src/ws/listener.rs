@@ -4,23 +4,41 @@ use binance::websockets::WebSockets;
 use iced_futures::futures::{channel::mpsc as mpsc_futures, SinkExt};
 use serde::de::DeserializeOwned;
 use tokio::sync::mpsc as mpsc_tokio;
-use tracing::info;
+use tracing::{info, warn};
 
-use super::{WsEvent, WsHandle, WsMessage};
+use super::{Reconnect, Stream, WsEvent, WsHandle, WsMessage};
 
 pub(crate) trait WsListener {
     type Event: Send + DeserializeOwned;
     type Input;
     type Output;
 
+    /// Feed this listener provides, used to track its health
+    fn stream(&self) -> Stream;
+
     /// Wrap `WsEvent` in correct variant of `WsMessage`
     fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage;
 
     /// Endpoint given to `web_socket.connect`
-    async fn endpoint(&self) -> Result<String, Box<dyn Error + Send>>;
+    async fn endpoint(&mut self) -> Result<String, Box<dyn Error + Send>>;
+
+    /// Interval of `keepalive` calls while connected, `None` disables them
+    fn keepalive_period(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Extend server side session of endpoint
+    async fn keepalive(&mut self) {}
+
+    /// Endpoint is no longer valid, connection is reopened with new one
+    fn session_expired(&self) -> bool {
+        false
+    }
 
     /// Handle websocket event
-    fn handle_event(&self, event: Self::Event) -> Self::Output;
+    ///
+    /// Returning `None` swallows event without notifying app
+    async fn handle_event(&mut self, event: Self::Event) -> Option<Self::Output>;
 
     /// Handle message from input channel
     ///
@@ -28,10 +46,19 @@ pub(crate) trait WsListener {
     fn handle_input(&mut self, input: Self::Input, keep_running: &mut AtomicBool);
 
     /// Main entrypoint
-    async fn run(&mut self, mut output: mpsc_futures::Sender<WsMessage>) -> ! {
+    ///
+    /// Failed connections are retried with growing delay, market streams without data for too
+    /// long are reconnected.
+    async fn run(
+        &mut self,
+        mut output: mpsc_futures::Sender<WsMessage>,
+        reconnect: Reconnect,
+    ) -> ! {
         // forward messages out of websocket callback
         let (tx, mut rx) = mpsc_tokio::unbounded_channel();
 
+        // error type is dictated by binance callback signature
+        #[allow(clippy::result_large_err)]
         let mut web_socket = WebSockets::new(|event| {
             tx.send(event)
                 .map_err(|e| binance::errors::Error::Msg(e.to_string()))
@@ -42,6 +69,10 @@ pub(crate) trait WsListener {
         let connected = self.message(WsEvent::Created(WsHandle(input_tx)));
         let _ = output.send(connected).await;
 
+        // failed attempts since last message
+        let mut attempt = 0;
+        let watch_stale = !self.stream().is_event_driven();
+
         loop {
             let mut keep_running = AtomicBool::new(true);
 
@@ -56,7 +87,8 @@ pub(crate) trait WsListener {
                 Err(e) => {
                     tracing::error!("endpoint error: {e}");
 
-                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    tokio::time::sleep(reconnect.delay(attempt)).await;
+                    attempt += 1;
                     continue;
                 }
             };
@@ -64,12 +96,17 @@ pub(crate) trait WsListener {
             if let Err(e) = web_socket.connect(&endpoint).await {
                 tracing::error!("connection error: {e}");
 
-                tokio::time::sleep(Duration::from_secs(2)).await;
+                tokio::time::sleep(reconnect.delay(attempt)).await;
+                attempt += 1;
                 continue;
             }
 
             info!("connected {}", &endpoint);
-            let connected = self.message(WsEvent::Connected);
+            let mut keepalive = self.keepalive_period().map(|period| {
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+            });
+
+            let connected = self.message(WsEvent::Connected(self.stream()));
             let _ = output.send(connected).await;
 
             loop {
@@ -86,16 +123,46 @@ pub(crate) trait WsListener {
                         self.handle_input(input.expect("channel closed"), &mut keep_running);
                     }
                     event = rx.recv() => {
-                        let handled = self.handle_event(event.expect("channel closed"));
-                        let message = self.message(WsEvent::Message(handled));
-                        let _ = output.send(message).await;
+                        attempt = 0;
+                        if let Some(handled) = self.handle_event(event.expect("channel closed")).await {
+                            let message = self.message(WsEvent::Message(handled));
+                            let _ = output.send(message).await;
+                        }
+                        if self.session_expired() {
+                            break;
+                        }
+                    }
+                    _ = tick(&mut keepalive) => {
+                        self.keepalive().await;
+                        if self.session_expired() {
+                            break;
+                        }
+                    }
+                    // timer restarts with every event
+                    _ = tokio::time::sleep(reconnect.stale_after()), if watch_stale => {
+                        warn!("no data from {} for {:?}, reconnecting", self.stream(), reconnect.stale_after());
+                        let _ = web_socket.disconnect().await;
+                        break;
                     }
                 }
             }
 
             info!("disconnected {}", &endpoint);
-            let disconnected = self.message(WsEvent::Disconnected);
+            let disconnected = self.message(WsEvent::Disconnected(self.stream()));
             let _ = output.send(disconnected).await;
+
+            tokio::time::sleep(reconnect.delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+/// Wait for next tick, forever without interval
+async fn tick(interval: &mut Option<tokio::time::Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
         }
+        None => std::future::pending().await,
     }
 }
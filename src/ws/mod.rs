@@ -1,17 +1,34 @@
-use binance::rest_model::{Order, OrderStatus};
-use iced::Subscription;
+use std::collections::BTreeSet;
+
+use iced::{Command, Subscription};
 use ringbuf::Rb;
 use tokio::sync::mpsc;
 
+use binance::{
+    rest_model::OrderStatus,
+    ws_model::{OrderUpdate, WebsocketEvent},
+};
+use tracing::warn;
+
 use self::listener::WsListener;
-use crate::{data::AppData, message::Message, views::dashboard::DashboardView};
+use self::user::{ListEvent, ListOrderStatus, ListStatus, UserEvent};
+use crate::{
+    data::{AppData, ChartKey, Severity},
+    exchange::Order,
+    message::Message,
+};
 
 mod book;
+mod health;
+mod klines;
 mod listener;
+mod mux;
 pub(crate) mod prices;
 pub(crate) mod trades;
 mod user;
 
+pub(crate) use health::{FeedStatus, Reconnect, Stream, Streams};
+
 #[derive(Debug, Clone)]
 pub(crate) enum WsEvent<In, Out> {
     /// Websocket created
@@ -20,10 +37,10 @@ pub(crate) enum WsEvent<In, Out> {
     Created(WsHandle<In>),
 
     /// Connected successfully
-    Connected,
+    Connected(Stream),
 
-    /// Connection closed
-    Disconnected,
+    /// Connection closed, reconnect follows
+    Disconnected(Stream),
 
     /// Websocket message
     Message(Out),
@@ -35,10 +52,15 @@ pub(crate) enum WsMessage {
         WsEvent<<trades::TradesWs as WsListener>::Input, <trades::TradesWs as WsListener>::Output>,
     ),
     Book(WsEvent<<book::BookWs as WsListener>::Input, <book::BookWs as WsListener>::Output>),
+    Kline(
+        WsEvent<<klines::KlinesWs as WsListener>::Input, <klines::KlinesWs as WsListener>::Output>,
+    ),
     Price(
         WsEvent<<prices::PricesWs as WsListener>::Input, <prices::PricesWs as WsListener>::Output>,
     ),
     User(WsEvent<<user::UserWs as WsListener>::Input, <user::UserWs as WsListener>::Output>),
+    /// Market stream connection created, takes subscription changes
+    Mux(WsHandle<mux::Input>),
 }
 
 /// Allows communicating with websocket. If you drop this, ws will spin endlessly on closed channel
@@ -52,151 +74,248 @@ impl<T> WsHandle<T> {
 }
 
 pub(crate) struct Websockets {
-    currency_pair: String,
     api_key: String,
     user: Option<WsHandle<user::Message>>,
-    prices: Option<WsHandle<()>>,
-    book: Option<WsHandle<book::Message>>,
-    trade: Option<WsHandle<trades::Message>>,
+    mux: Option<WsHandle<mux::Input>>,
+    /// Market streams that should be subscribed
+    tracked: BTreeSet<Stream>,
+    /// Policy given to streams when they are opened
+    reconnect: Reconnect,
+    listen_key: user::ListenKey,
 }
 
 impl Websockets {
-    pub(crate) fn new(api_key: String, currency_pair: &str) -> Self {
+    pub(crate) fn new(api_key: String, reconnect: Reconnect) -> Self {
         Self {
             user: None,
-            prices: None,
-            book: None,
-            trade: None,
+            mux: None,
+            tracked: BTreeSet::new(),
             api_key,
-            currency_pair: currency_pair.to_lowercase(),
+            reconnect,
+            listen_key: Default::default(),
+        }
+    }
+
+    /// Takes effect for streams opened from now on, market streams use it after reconnect
+    pub(crate) fn set_reconnect(&mut self, reconnect: Reconnect) {
+        self.reconnect = reconnect;
+        if let Some(mux) = &self.mux {
+            mux.send(mux::Input::Reconnect(reconnect));
         }
     }
 
-    pub(crate) fn relogin_user(&self, api_key: &str) {
+    /// Subscribe book and trades of each of `symbols` and klines of each chart
+    ///
+    /// Streams no longer needed are unsubscribed, connection stays open. Their data is dropped, as
+    /// it would go stale.
+    pub(crate) fn track(
+        &mut self,
+        symbols: BTreeSet<String>,
+        charts: BTreeSet<ChartKey>,
+        data: &mut AppData,
+    ) {
+        data.books.retain(|symbol, _| symbols.contains(symbol));
+        data.trades.retain(|symbol, _| symbols.contains(symbol));
+        data.price_charts.retain(|key, _| charts.contains(key));
+
+        let tracked: BTreeSet<_> = symbols
+            .into_iter()
+            .flat_map(|symbol| [Stream::Trades(symbol.clone()), Stream::Book(symbol)])
+            .chain(charts.into_iter().map(Stream::Klines))
+            .chain([Stream::Prices])
+            .collect();
+
+        if tracked == self.tracked {
+            return;
+        }
+        self.tracked = tracked;
+        if let Some(mux) = &self.mux {
+            mux.send(mux::Input::Track(self.tracked.clone()));
+        }
+    }
+
+    /// Close listen key of old credentials and open user stream with new ones
+    pub(crate) fn relogin_user(&mut self, api_key: &str) {
+        self.api_key = api_key.to_owned();
         if let Some(ws_user) = &self.user {
             ws_user.send(user::Message::NewApiKey(api_key.to_owned()));
         };
     }
 
-    pub(crate) fn track_new_currency_pair(&self, pair: &str) {
-        let pair = pair.to_lowercase();
+    /// Close listen key, so exchange stops user stream, then exit
+    pub(crate) fn shutdown(&self) -> Command<Message> {
+        let listen_key = self.listen_key.lock().unwrap().take();
+        let api_key = self.api_key.clone();
 
-        if let Some(book_ws) = &self.book {
-            book_ws.send(book::Message::NewPair(pair.clone()));
-        };
-        if let Some(ws_trade) = &self.trade {
-            ws_trade.send(trades::Message::NewPair(pair));
-        };
+        Command::perform(
+            async move {
+                if let Some(listen_key) = listen_key {
+                    user::close_listen_key(api_key, listen_key).await;
+                }
+            },
+            |_| Message::Exit,
+        )
     }
 
+    /// Market streams share one connection, user stream has its own
     pub(crate) fn subscription(&self) -> Subscription<Message> {
         Subscription::batch([
-            trades::connect(self.currency_pair.clone()),
-            book::connect(self.currency_pair.clone()),
-            prices::connect(),
-            user::connect(self.api_key.clone()),
+            mux::connect(self.reconnect),
+            user::connect(
+                self.api_key.clone(),
+                self.listen_key.clone(),
+                self.reconnect,
+            ),
         ])
         .map(Message::from)
     }
 
-    pub(crate) fn update(
-        &mut self,
-        msg: WsMessage,
-        data: &mut AppData,
-        dashboard: &mut DashboardView,
-    ) {
+    pub(crate) fn update(&mut self, msg: WsMessage, data: &mut AppData) {
         match msg {
             WsMessage::Book(event) => {
                 match event {
-                    WsEvent::Created(handle) => self.book = Some(handle),
-                    WsEvent::Message(bt) => {
-                        data.book = (bt.sym, bt.bids, bt.asks);
+                    WsEvent::Created(_) => (),
+                    WsEvent::Message(book) => {
+                        data.streams.message(Stream::Book(book.sym.clone()));
+                        data.books.insert(book.sym.clone(), book);
                     }
-                    WsEvent::Connected | WsEvent::Disconnected => (),
+                    WsEvent::Connected(stream) => stream_status(data, stream, true),
+                    WsEvent::Disconnected(stream) => stream_status(data, stream, false),
                 };
             }
+            WsMessage::Kline(event) => match event {
+                WsEvent::Created(_) => (),
+                WsEvent::Message((key, kline)) => {
+                    data.streams.message(Stream::Klines(key.clone()));
+                    // charts are created by history request, which replaces earlier candles
+                    if let Some(chart) = data.price_charts.get_mut(&key) {
+                        chart.upsert(kline);
+                    }
+                }
+                WsEvent::Connected(stream) => stream_status(data, stream, true),
+                WsEvent::Disconnected(stream) => stream_status(data, stream, false),
+            },
             WsMessage::Trade(event) => match event {
-                WsEvent::Created(handle) => self.trade = Some(handle),
+                WsEvent::Created(_) => (),
                 WsEvent::Message(te) => {
-                    data.trades.push_overwrite(te);
+                    data.streams.message(Stream::Trades(te.symbol.clone()));
+                    data.trades
+                        .entry(te.symbol.clone())
+                        .or_default()
+                        .push_overwrite(te);
                 }
-                WsEvent::Connected | WsEvent::Disconnected => (),
+                WsEvent::Connected(stream) => stream_status(data, stream, true),
+                WsEvent::Disconnected(stream) => stream_status(data, stream, false),
             },
             WsMessage::User(event) => match event {
                 WsEvent::Created(handle) => self.user = Some(handle),
-                WsEvent::Message(msg) => match msg {
-                    binance::ws_model::WebsocketEvent::AccountPositionUpdate(p) => {
-                        for b in p.balances {
-                            let ib = data.balances.iter_mut().find(|a| a.asset == b.asset);
-                            if let Some(uib) = ib {
-                                *uib = unsafe { std::mem::transmute(b) }
-                            }
-                        }
-                    }
-                    binance::ws_model::WebsocketEvent::OrderUpdate(o) => {
-                        let existing_order = data.orders.iter_mut().find(|order| {
-                            // order.client_order_id == o.order_id&&
-                            order.symbol == o.symbol
-                                && order.side == o.side
-                                && order.status == OrderStatus::PartiallyFilled
-                        });
-
-                        if let Some(order) = existing_order {
-                            // Update the existing order with the new values
-                            order.executed_qty += o.qty_last_executed;
-                            order.cummulative_quote_qty += o.qty;
-                            order.update_time = o.trade_order_time;
-                        } else {
-                            data.orders.insert(
-                                0,
-                                Order {
-                                    symbol: o.symbol,
-                                    order_id: o.order_id,
-                                    order_list_id: o.order_list_id as i32,
-                                    client_order_id: o.client_order_id.unwrap(),
-                                    price: o.price,
-                                    orig_qty: o.qty,
-                                    executed_qty: o.qty_last_executed,
-                                    cummulative_quote_qty: o.qty,
-                                    status: o.current_order_status,
-                                    time_in_force: o.time_in_force,
-                                    order_type: o.order_type,
-                                    side: o.side,
-                                    stop_price: o.stop_price,
-                                    iceberg_qty: o.iceberg_qty,
-                                    time: o.event_time,
-                                    update_time: o.trade_order_time,
-                                    is_working: false,
-                                    orig_quote_order_qty: o.qty,
-                                },
-                            );
-                        }
+                WsEvent::Message(msg) => {
+                    data.streams.message(Stream::User);
+                    match msg {
+                        UserEvent::Known(event) => user_event(data, event),
+                        UserEvent::List(ListEvent::Status(list)) => list_status(data, list),
+                        UserEvent::Other { event } => warn!("unhandled user stream event {event}"),
                     }
-                    binance::ws_model::WebsocketEvent::BalanceUpdate(_p) => {
-                        // not needed imo?
-                    }
-                    binance::ws_model::WebsocketEvent::ListOrderUpdate(_lo) => {
-                        // not needed imo?
-                    }
-                    _ => unreachable!(),
-                },
-                WsEvent::Connected | WsEvent::Disconnected => (),
+                }
+                WsEvent::Connected(stream) => stream_status(data, stream, true),
+                WsEvent::Disconnected(stream) => stream_status(data, stream, false),
             },
+            WsMessage::Mux(handle) => {
+                handle.send(mux::Input::Track(self.tracked.clone()));
+                self.mux = Some(handle);
+            }
             WsMessage::Price(m) => {
                 match m {
-                    WsEvent::Created(handle) => self.prices = Some(handle),
+                    WsEvent::Created(_) => (),
                     WsEvent::Message(assets) => {
-                        for asset in &assets {
-                            if asset.name == dashboard.pair() {
-                                data.price_chart.push_overwrite(f64::from(asset.price));
-                                break;
-                            }
-                        }
+                        data.streams.message(Stream::Prices);
                         data.prices.add_many(assets);
                     }
-                    WsEvent::Connected | WsEvent::Disconnected => (),
+                    WsEvent::Connected(stream) => stream_status(data, stream, true),
+                    WsEvent::Disconnected(stream) => stream_status(data, stream, false),
                 };
             }
         }
     }
 }
+
+fn user_event(data: &mut AppData, event: WebsocketEvent) {
+    match event {
+        WebsocketEvent::AccountPositionUpdate(p) => {
+            for b in p.balances {
+                // same filter as balances loaded over rest
+                let held = b.free + b.locked > 0.0;
+                data.set_balance(b.into(), held);
+            }
+        }
+        WebsocketEvent::OrderUpdate(o) => {
+            execution_notification(data, &o);
+            data.apply_order_update(Order::from(*o));
+        }
+        WebsocketEvent::BalanceUpdate(b) => {
+            // deposits and withdrawals, new totals follow in account position update
+            data.notifications.push(
+                Severity::Info,
+                "balance",
+                format!("{} {:+}", b.asset, b.delta),
+            );
+        }
+        // parsed as our own list status, since binance crate keeps its fields private
+        WebsocketEvent::ListOrderUpdate(_) => (),
+        other => warn!("unexpected user stream event {other:?}"),
+    }
+}
+
+/// Report fills and closes, placing is reported by the app itself
+fn execution_notification(data: &mut AppData, o: &OrderUpdate) {
+    let text = match o.execution_type {
+        OrderStatus::Trade => format!(
+            "{:?} {} @ {} filled",
+            o.side, o.qty_last_executed, o.last_executed_price
+        ),
+        OrderStatus::Canceled => "canceled".to_owned(),
+        OrderStatus::Rejected => format!("rejected: {}", o.order_reject_reason),
+        OrderStatus::Expired => "expired".to_owned(),
+        _ => return,
+    };
+    let severity = match o.execution_type {
+        OrderStatus::Rejected => Severity::Warning,
+        _ => Severity::Info,
+    };
+
+    data.notifications
+        .push(severity, format!("{} order {}", o.symbol, o.order_id), text);
+}
+
+/// Legs of finished OCO list are no longer open, even if their own updates were missed
+fn list_status(data: &mut AppData, list: ListStatus) {
+    match list.status {
+        ListOrderStatus::Executing => (),
+        // legs share symbol of the list, ids are only unique per symbol
+        ListOrderStatus::AllDone => data.open_orders.retain(|o| {
+            o.symbol != list.symbol || !list.orders.iter().any(|leg| leg.order_id == o.order_id)
+        }),
+        ListOrderStatus::Reject => data.notifications.push(
+            Severity::Error,
+            format!("{} OCO {}", list.symbol, list.order_list_id),
+            format!("rejected: {}", list.reject_reason),
+        ),
+    }
+}
+
+/// Track and log websocket connection changes
+fn stream_status(data: &mut AppData, stream: Stream, connected: bool) {
+    let (severity, status) = if connected {
+        (Severity::Info, "connected")
+    } else {
+        (Severity::Warning, "disconnected")
+    };
+
+    data.notifications
+        .push(severity, format!("{stream} stream"), status.to_owned());
+    if connected {
+        data.streams.connected(stream);
+    } else {
+        data.streams.disconnected(stream);
+    }
+}
@@ -0,0 +1,519 @@
+//! Market streams multiplexed over one combined stream connection
+//!
+//! Streams are added and removed with SUBSCRIBE and UNSUBSCRIBE requests, so switching pairs does
+//! not reconnect. Frames are routed by stream name to listener of that stream.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{Duration, Instant},
+};
+
+use binance::{
+    config::Config, rest_model::OrderBook as DepthSnapshot, websockets::STREAM_ENDPOINT,
+};
+use iced::subscription::{self, Subscription};
+use iced_futures::futures::{channel::mpsc as mpsc_futures, Sink, SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc as mpsc_tokio;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{error, info, warn};
+
+use super::{
+    book::BookWs, klines::KlinesWs, prices::PricesWs, trades::TradesWs, Reconnect, Stream, WsEvent,
+    WsHandle, WsListener, WsMessage,
+};
+
+#[derive(Debug, Clone)]
+pub(crate) enum Input {
+    /// Streams to keep subscribed, others are unsubscribed
+    Track(BTreeSet<Stream>),
+    /// Policy used from next reconnect on
+    Reconnect(Reconnect),
+    /// Book snapshot fetched apart from connection
+    Snapshot {
+        stream: String,
+        result: Result<DepthSnapshot, String>,
+    },
+}
+
+/// Consumer of one market stream
+enum Listener {
+    Prices(PricesWs),
+    Trades(TradesWs),
+    Book(Box<BookWs>),
+    Klines(KlinesWs),
+}
+
+/// Evaluate `$body` with `$l` bound to listener of any kind
+macro_rules! with_listener {
+    ($listener:expr, $l:ident => $body:expr) => {
+        match $listener {
+            Listener::Prices($l) => $body,
+            Listener::Trades($l) => $body,
+            Listener::Book($l) => $body,
+            Listener::Klines($l) => $body,
+        }
+    };
+}
+
+impl Listener {
+    fn new(stream: Stream, reconnect: Reconnect) -> Option<Self> {
+        match stream {
+            Stream::Prices => Some(Self::Prices(PricesWs::new())),
+            Stream::Trades(pair) => Some(Self::Trades(TradesWs::new(pair))),
+            Stream::Book(pair) => Some(Self::Book(Box::new(BookWs::new(pair, reconnect)))),
+            Stream::Klines(key) => Some(Self::Klines(KlinesWs::new(key))),
+            // listen key has its own connection
+            Stream::User => None,
+        }
+    }
+
+    fn stream(&self) -> Stream {
+        with_listener!(self, l => l.stream())
+    }
+
+    /// Name of stream on combined connection
+    async fn name(&mut self) -> Option<String> {
+        with_listener!(self, l => match l.endpoint().await {
+            Ok(name) => Some(name),
+            Err(e) => {
+                error!("endpoint error: {e}");
+                None
+            }
+        })
+    }
+
+    fn status(&self, connected: bool) -> WsMessage {
+        with_listener!(self, l => {
+            let event = if connected {
+                WsEvent::Connected(l.stream())
+            } else {
+                WsEvent::Disconnected(l.stream())
+            };
+            l.message(event)
+        })
+    }
+
+    async fn handle(&mut self, data: Value) -> Option<WsMessage> {
+        match self {
+            Self::Prices(l) => forward(l, data).await,
+            Self::Trades(l) => forward(l, data).await,
+            Self::Book(l) => forward(l.as_mut(), data).await,
+            Self::Klines(l) => forward(l, data).await,
+        }
+    }
+}
+
+/// Pass stream payload to listener, wrapping its output for app
+async fn forward<L: WsListener>(listener: &mut L, data: Value) -> Option<WsMessage> {
+    match serde_json::from_value(data) {
+        Ok(event) => {
+            let output = listener.handle_event(event).await?;
+            Some(listener.message(WsEvent::Message(output)))
+        }
+        Err(e) => {
+            warn!("malformed {} event: {e}", listener.stream());
+            None
+        }
+    }
+}
+
+/// Text frame of combined stream
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Incoming {
+    Data {
+        stream: String,
+        data: Value,
+    },
+    /// Answer to subscription request
+    Reply {
+        id: Option<u64>,
+        #[serde(default)]
+        error: Option<Value>,
+    },
+}
+
+struct Route {
+    listener: Listener,
+    last_frame: Instant,
+}
+
+struct Mux {
+    /// Subscribed streams by name
+    routes: BTreeMap<String, Route>,
+    /// Id of last subscription request
+    request_id: u64,
+    /// Sends fetched book snapshots back to connection loop
+    input: mpsc_tokio::UnboundedSender<Input>,
+}
+
+impl Mux {
+    fn new(input: mpsc_tokio::UnboundedSender<Input>) -> Self {
+        Self {
+            routes: BTreeMap::new(),
+            request_id: 0,
+            input,
+        }
+    }
+
+    /// Open listeners of new streams and drop removed ones, returns names added and removed
+    async fn track(
+        &mut self,
+        wanted: &BTreeSet<Stream>,
+        reconnect: Reconnect,
+    ) -> (Vec<String>, Vec<String>) {
+        let removed: Vec<_> = self
+            .routes
+            .iter()
+            .filter(|(_, route)| !wanted.contains(&route.listener.stream()))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &removed {
+            self.routes.remove(name);
+        }
+
+        let known: BTreeSet<_> = self
+            .routes
+            .values()
+            .map(|route| route.listener.stream())
+            .collect();
+
+        let mut added = Vec::new();
+        for stream in wanted.difference(&known) {
+            let Some(mut listener) = Listener::new(stream.clone(), reconnect) else {
+                continue;
+            };
+            let Some(name) = listener.name().await else {
+                continue;
+            };
+
+            let route = Route {
+                listener,
+                last_frame: Instant::now(),
+            };
+            self.routes.insert(name.clone(), route);
+            added.push(name);
+        }
+
+        (added, removed)
+    }
+
+    /// Subscription change, `None` when there is nothing to change
+    fn request(&mut self, method: &str, names: Vec<String>) -> Option<Message> {
+        if names.is_empty() {
+            return None;
+        }
+
+        self.request_id += 1;
+        let request = json!({ "method": method, "params": names, "id": self.request_id });
+        Some(Message::Text(request.to_string()))
+    }
+
+    async fn dispatch(&mut self, text: &str) -> Option<WsMessage> {
+        match serde_json::from_str(text) {
+            Ok(Incoming::Data { stream, data }) => {
+                // frames of just unsubscribed stream may still arrive
+                let route = self.routes.get_mut(&stream)?;
+                route.last_frame = Instant::now();
+
+                let message = route.listener.handle(data).await;
+                if let Listener::Book(book) = &mut route.listener {
+                    request_snapshot(&self.input, stream, book);
+                }
+                message
+            }
+            Ok(Incoming::Reply {
+                id,
+                error: Some(error),
+            }) => {
+                warn!("market subscription request {id:?} failed: {error}");
+                None
+            }
+            Ok(Incoming::Reply { .. }) => None,
+            Err(e) => {
+                warn!("malformed market frame: {e}");
+                None
+            }
+        }
+    }
+
+    /// Pass fetched snapshot to its book, returns book once it is synced
+    fn snapshot_loaded(
+        &mut self,
+        stream: &str,
+        result: Result<DepthSnapshot, String>,
+    ) -> Option<WsMessage> {
+        // book may have been unsubscribed while snapshot loaded
+        let Listener::Book(book) = &mut self.routes.get_mut(stream)?.listener else {
+            return None;
+        };
+        // on failure next diff requests snapshot again, once retry delay passes
+        let output = book.snapshot_loaded(result)?;
+        Some(book.message(WsEvent::Message(output)))
+    }
+
+    /// Streams expected to send data that were silent for longer than `after`
+    fn stale(&self, after: Duration) -> Vec<String> {
+        self.routes
+            .iter()
+            .filter(|(_, route)| {
+                !route.listener.stream().is_event_driven() && route.last_frame.elapsed() > after
+            })
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    /// Number of streams watched by `stale`
+    fn watched(&self) -> usize {
+        self.routes
+            .values()
+            .filter(|route| !route.listener.stream().is_event_driven())
+            .count()
+    }
+
+    fn reset_timers<'a>(&mut self, names: impl IntoIterator<Item = &'a String>) {
+        let now = Instant::now();
+        for name in names {
+            if let Some(route) = self.routes.get_mut(name) {
+                route.last_frame = now;
+            }
+        }
+    }
+}
+
+/// Fetch snapshot of unsynced book in own task, result comes back as `Input::Snapshot`
+fn request_snapshot(input: &mpsc_tokio::UnboundedSender<Input>, stream: String, book: &mut BookWs) {
+    let Some(request) = book.snapshot_request() else {
+        return;
+    };
+
+    let input = input.clone();
+    tokio::spawn(async move {
+        let result = request.await;
+        let _ = input.send(Input::Snapshot { stream, result });
+    });
+}
+
+/// Apply input to connection state, returns book synced by loaded snapshot
+fn apply(
+    mux: &mut Mux,
+    input: Input,
+    wanted: &mut BTreeSet<Stream>,
+    reconnect: &mut Reconnect,
+) -> Option<WsMessage> {
+    match input {
+        Input::Track(streams) => *wanted = streams,
+        Input::Reconnect(policy) => *reconnect = policy,
+        Input::Snapshot { stream, result } => return mux.snapshot_loaded(&stream, result),
+    }
+    None
+}
+
+async fn send_requests<S: Sink<Message> + Unpin>(
+    socket: &mut S,
+    requests: impl IntoIterator<Item = Option<Message>>,
+) -> Result<(), S::Error> {
+    for request in requests.into_iter().flatten() {
+        socket.send(request).await?;
+    }
+    Ok(())
+}
+
+/// Keeps connection open while any stream is tracked
+///
+/// Failed connections are retried with growing delay. Streams without data for too long are
+/// resubscribed, connection is reopened when all of them are silent.
+async fn run(mut output: mpsc_futures::Sender<WsMessage>, mut reconnect: Reconnect) -> ! {
+    let (input_tx, mut input_rx) = mpsc_tokio::unbounded_channel();
+    let mut mux = Mux::new(input_tx.clone());
+    let _ = output.send(WsMessage::Mux(WsHandle(input_tx))).await;
+
+    let mut wanted = BTreeSet::new();
+    // failed attempts since last frame
+    let mut attempt = 0;
+
+    loop {
+        // changes made while disconnected go to connection url
+        while let Ok(input) = input_rx.try_recv() {
+            if let Some(message) = apply(&mut mux, input, &mut wanted, &mut reconnect) {
+                let _ = output.send(message).await;
+            }
+        }
+        mux.track(&wanted, reconnect).await;
+
+        if mux.routes.is_empty() {
+            let input = input_rx.recv().await.expect("channel closed");
+            if let Some(message) = apply(&mut mux, input, &mut wanted, &mut reconnect) {
+                let _ = output.send(message).await;
+            }
+            continue;
+        }
+
+        let names: Vec<_> = mux.routes.keys().cloned().collect();
+        let url = format!(
+            "{}/{STREAM_ENDPOINT}?streams={}",
+            Config::default().ws_endpoint,
+            names.join("/")
+        );
+
+        let mut socket = match connect_async(url.as_str()).await {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                error!("market connection error: {e}");
+
+                tokio::time::sleep(reconnect.delay(attempt)).await;
+                attempt += 1;
+                continue;
+            }
+        };
+
+        info!("connected {} market streams", names.len());
+        mux.reset_timers(&names);
+        for route in mux.routes.values() {
+            let _ = output.send(route.listener.status(true)).await;
+        }
+
+        let mut stale_check = tokio::time::interval(reconnect.stale_after());
+
+        loop {
+            tokio::select! {
+                frame = socket.next() => match frame {
+                    Some(Ok(Message::Text(text))) => {
+                        attempt = 0;
+                        if let Some(message) = mux.dispatch(&text).await {
+                            let _ = output.send(message).await;
+                        }
+                    }
+                    Some(Ok(Message::Close(reason))) => {
+                        warn!("market connection closed: {reason:?}");
+                        break;
+                    }
+                    // pings are answered by socket itself
+                    Some(Ok(_)) => (),
+                    Some(Err(e)) => {
+                        error!("market stream error: {e}");
+                        break;
+                    }
+                    None => break,
+                },
+                input = input_rx.recv() => {
+                    let mut input = input.expect("channel closed");
+                    // exchange limits incoming messages, so bursts of changes go in one request
+                    loop {
+                        if let Some(message) = apply(&mut mux, input, &mut wanted, &mut reconnect) {
+                            let _ = output.send(message).await;
+                        }
+                        match input_rx.try_recv() {
+                            Ok(next) => input = next,
+                            Err(_) => break,
+                        }
+                    }
+
+                    let (added, removed) = mux.track(&wanted, reconnect).await;
+                    for name in &added {
+                        let _ = output.send(mux.routes[name].listener.status(true)).await;
+                    }
+
+                    let requests = [
+                        mux.request("UNSUBSCRIBE", removed),
+                        mux.request("SUBSCRIBE", added),
+                    ];
+                    if let Err(e) = send_requests(&mut socket, requests).await {
+                        error!("market subscription error: {e}");
+                        break;
+                    }
+                }
+                _ = stale_check.tick() => {
+                    let stale = mux.stale(reconnect.stale_after());
+                    if stale.is_empty() {
+                        continue;
+                    }
+
+                    if stale.len() == mux.watched() {
+                        warn!("no market data for {:?}, reconnecting", reconnect.stale_after());
+                        let _ = socket.close(None).await;
+                        break;
+                    }
+
+                    warn!("no data from {stale:?}, resubscribing");
+                    mux.reset_timers(&stale);
+                    let requests = [
+                        mux.request("UNSUBSCRIBE", stale.clone()),
+                        mux.request("SUBSCRIBE", stale),
+                    ];
+                    if let Err(e) = send_requests(&mut socket, requests).await {
+                        error!("market subscription error: {e}");
+                        break;
+                    }
+                }
+            }
+        }
+
+        info!("disconnected market streams");
+        for route in mux.routes.values() {
+            let _ = output.send(route.listener.status(false)).await;
+        }
+
+        tokio::time::sleep(reconnect.delay(attempt)).await;
+        attempt += 1;
+    }
+}
+
+/// Single connection shared by all market streams
+pub(crate) fn connect(reconnect: Reconnect) -> Subscription<WsMessage> {
+    struct Connect;
+
+    subscription::channel(
+        std::any::TypeId::of::<Connect>(),
+        100,
+        move |output| async move { run(output, reconnect).await },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_are_told_apart() {
+        let data: Incoming =
+            serde_json::from_str(r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade"}}"#)
+                .unwrap();
+        assert!(matches!(data, Incoming::Data { stream, .. } if stream == "btcusdt@aggTrade"));
+
+        let ok: Incoming = serde_json::from_str(r#"{"result":null,"id":3}"#).unwrap();
+        assert!(matches!(
+            ok,
+            Incoming::Reply {
+                id: Some(3),
+                error: None
+            }
+        ));
+
+        let failed: Incoming =
+            serde_json::from_str(r#"{"error":{"code":2,"msg":"Invalid request"},"id":null}"#)
+                .unwrap();
+        assert!(matches!(
+            failed,
+            Incoming::Reply {
+                id: None,
+                error: Some(_)
+            }
+        ));
+
+        let (input, _) = mpsc_tokio::unbounded_channel();
+        let mut mux = Mux::new(input);
+        assert!(mux.request("SUBSCRIBE", Vec::new()).is_none());
+        let Some(Message::Text(request)) =
+            mux.request("SUBSCRIBE", vec!["btcusdt@depth".to_owned()])
+        else {
+            panic!("no request");
+        };
+        assert_eq!(
+            request,
+            r#"{"id":1,"method":"SUBSCRIBE","params":["btcusdt@depth"]}"#
+        );
+    }
+}
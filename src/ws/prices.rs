@@ -1,12 +1,11 @@
 use std::{error::Error, sync::atomic::AtomicBool};
 
 use binance::websockets::all_ticker_stream;
-use iced::subscription::{self, Subscription};
 use serde::{de, Deserialize, Deserializer};
 
 use crate::ws::WsEvent;
 
-use super::{WsListener, WsMessage};
+use super::{Stream, WsListener, WsMessage};
 
 fn str_as_f32<'de, D>(deserializer: D) -> Result<f32, D::Error>
 where
@@ -39,25 +38,21 @@ impl WsListener for PricesWs {
     type Input = ();
     type Output = Vec<AssetDetails>;
 
+    fn stream(&self) -> Stream {
+        Stream::Prices
+    }
+
     fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage {
         WsMessage::Price(msg)
     }
 
-    async fn endpoint(&self) -> Result<String, Box<dyn Error + Send>> {
+    async fn endpoint(&mut self) -> Result<String, Box<dyn Error + Send>> {
         Ok(all_ticker_stream().to_owned())
     }
 
-    fn handle_event(&self, event: Self::Event) -> Self::Output {
-        event
+    async fn handle_event(&mut self, event: Self::Event) -> Option<Self::Output> {
+        Some(event)
     }
 
     fn handle_input(&mut self, _: Self::Input, _: &mut AtomicBool) {}
 }
-
-pub fn connect() -> Subscription<WsMessage> {
-    struct Connect;
-
-    subscription::channel(std::any::TypeId::of::<Connect>(), 100, |output| async {
-        PricesWs::new().run(output).await
-    })
-}
@@ -1,20 +1,15 @@
 use std::{error::Error, sync::atomic::AtomicBool};
 
 use binance::websockets::agg_trade_stream;
-use iced::subscription::{self, Subscription};
 use serde::{de, Deserialize, Deserializer};
 
 use crate::ws::WsEvent;
 
-use super::{WsListener, WsMessage};
-
-#[derive(Debug, Clone)]
-pub(crate) enum Message {
-    NewPair(String),
-}
+use super::{Stream, WsListener, WsMessage};
 
 #[derive(Debug)]
 pub(crate) struct TradesWs {
+    /// Uppercase symbol
     pair: String,
 }
 
@@ -46,6 +41,9 @@ where
 
 #[derive(Debug, Deserialize, Clone)]
 pub(crate) struct TradesEvent {
+    #[serde(rename = "s")]
+    pub(crate) symbol: String,
+
     #[serde(rename = "p", deserialize_with = "str_as_f32_as_str_formatted")]
     pub(crate) price: String,
 
@@ -61,35 +59,24 @@ pub(crate) struct TradesEvent {
 
 impl WsListener for TradesWs {
     type Event = TradesEvent;
-    type Input = Message;
+    type Input = ();
     type Output = TradesEvent;
 
-    fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage {
-        WsMessage::Trade(msg)
+    fn stream(&self) -> Stream {
+        Stream::Trades(self.pair.clone())
     }
 
-    async fn endpoint(&self) -> Result<String, Box<dyn Error + Send>> {
-        Ok(agg_trade_stream(&self.pair))
+    fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage {
+        WsMessage::Trade(msg)
     }
 
-    fn handle_event(&self, event: Self::Event) -> Self::Output {
-        event
+    async fn endpoint(&mut self) -> Result<String, Box<dyn Error + Send>> {
+        Ok(agg_trade_stream(&self.pair.to_lowercase()))
     }
 
-    fn handle_input(&mut self, input: Self::Input, keep_running: &mut AtomicBool) {
-        match input {
-            Message::NewPair(new_pair) => {
-                self.pair = new_pair;
-                keep_running.store(false, std::sync::atomic::Ordering::Relaxed);
-            }
-        };
+    async fn handle_event(&mut self, event: Self::Event) -> Option<Self::Output> {
+        Some(event)
     }
-}
-
-pub(crate) fn connect(pair: String) -> Subscription<WsMessage> {
-    struct Connect;
 
-    subscription::channel(std::any::TypeId::of::<Connect>(), 100, |output| async {
-        TradesWs::new(pair).run(output).await
-    })
+    fn handle_input(&mut self, _: Self::Input, _: &mut AtomicBool) {}
 }
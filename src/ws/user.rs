@@ -1,65 +1,251 @@
-use std::{error::Error, sync::atomic::AtomicBool};
+use std::{
+    error::Error,
+    sync::{atomic::AtomicBool, Arc, Mutex},
+    time::Duration,
+};
 
 use binance::{api::Binance, userstream::UserStream, ws_model::WebsocketEvent};
 use iced::subscription::{self, Subscription};
+use serde::Deserialize;
+use tracing::{info, warn};
 
 use crate::ws::WsEvent;
 
-use super::{WsListener, WsMessage};
+use super::{Reconnect, Stream, WsListener, WsMessage};
+
+/// Exchange drops listen key that was not kept alive for an hour
+const KEEPALIVE_PERIOD: Duration = Duration::from_secs(30 * 60);
+
+/// Give up closing listen key on shutdown after this long
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Listen key of running stream, shared with app so it can be closed on shutdown
+pub(crate) type ListenKey = Arc<Mutex<Option<String>>>;
 
 #[derive(Debug, Clone)]
 pub(crate) enum Message {
     NewApiKey(String),
 }
 
+/// User stream event, ones not known to binance crate are kept by name
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum UserEvent {
+    /// Parsed here, binance crate does not expose list status
+    List(ListEvent),
+    Known(WebsocketEvent),
+    Other {
+        #[serde(rename = "e")]
+        event: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "e")]
+pub(crate) enum ListEvent {
+    #[serde(rename = "listStatus")]
+    Status(ListStatus),
+}
+
+/// Update of OCO order list
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ListStatus {
+    #[serde(rename = "s")]
+    pub(crate) symbol: String,
+    #[serde(rename = "g")]
+    pub(crate) order_list_id: i64,
+    #[serde(rename = "L")]
+    pub(crate) status: ListOrderStatus,
+    #[serde(rename = "r")]
+    pub(crate) reject_reason: String,
+    #[serde(rename = "O")]
+    pub(crate) orders: Vec<ListLeg>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(crate) enum ListOrderStatus {
+    Executing,
+    AllDone,
+    Reject,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct ListLeg {
+    #[serde(rename = "i")]
+    pub(crate) order_id: u64,
+}
+
 #[derive(Debug)]
 pub(crate) struct UserWs {
     api_key: String,
+    listen_key: ListenKey,
+    /// Key of previous credentials with their api key, closed before new key is created
+    retired: Option<(String, String)>,
 }
 
 impl UserWs {
-    fn new(api_key: String) -> Self {
-        Self { api_key }
+    fn new(api_key: String, listen_key: ListenKey) -> Self {
+        Self {
+            api_key,
+            listen_key,
+            retired: None,
+        }
+    }
+
+    fn current_key(&self) -> Option<String> {
+        self.listen_key.lock().unwrap().clone()
+    }
+
+    /// Forget key, so new one is created on reconnect
+    fn expire_key(&self) {
+        self.listen_key.lock().unwrap().take();
+    }
+}
+
+fn user_stream(api_key: &str) -> UserStream {
+    Binance::new(Some(api_key.to_owned()), None)
+}
+
+/// Invalidate listen key, errors are only logged since key expires on its own
+pub(crate) async fn close_listen_key(api_key: String, listen_key: String) {
+    let user_stream = user_stream(&api_key);
+
+    match tokio::time::timeout(CLOSE_TIMEOUT, user_stream.close(&listen_key)).await {
+        Ok(Ok(_)) => info!("closed listen key"),
+        Ok(Err(e)) => warn!("failed to close listen key: {e}"),
+        Err(_) => warn!("closing listen key timed out"),
     }
 }
 
 impl WsListener for UserWs {
-    type Event = WebsocketEvent;
+    type Event = UserEvent;
     type Input = Message;
-    type Output = WebsocketEvent;
+    type Output = UserEvent;
+
+    fn stream(&self) -> Stream {
+        Stream::User
+    }
 
     fn message(&self, msg: WsEvent<Self::Input, Self::Output>) -> WsMessage {
         WsMessage::User(msg)
     }
 
-    async fn endpoint(&self) -> Result<String, Box<dyn Error + Send>> {
-        let user_stream: UserStream = Binance::new(Some(self.api_key.clone()), None);
+    async fn endpoint(&mut self) -> Result<String, Box<dyn Error + Send>> {
+        if let Some((api_key, listen_key)) = self.retired.take() {
+            close_listen_key(api_key, listen_key).await;
+        }
 
-        user_stream
+        // exchange returns the same key while it is valid, so reconnects keep it
+        let listen_key = user_stream(&self.api_key)
             .start()
             .await
             .map(|answer| answer.listen_key)
-            .map_err(|e| Box::new(e) as _)
+            .map_err(|e| Box::new(e) as _)?;
+
+        *self.listen_key.lock().unwrap() = Some(listen_key.clone());
+        Ok(listen_key)
+    }
+
+    fn keepalive_period(&self) -> Option<Duration> {
+        Some(KEEPALIVE_PERIOD)
+    }
+
+    async fn keepalive(&mut self) {
+        let Some(listen_key) = self.current_key() else {
+            return;
+        };
+
+        if let Err(e) = user_stream(&self.api_key).keep_alive(&listen_key).await {
+            warn!("listen key keepalive failed, creating new key: {e}");
+            self.expire_key();
+        }
+    }
+
+    fn session_expired(&self) -> bool {
+        self.current_key().is_none()
     }
 
-    fn handle_event(&self, event: Self::Event) -> Self::Output {
-        event
+    async fn handle_event(&mut self, event: Self::Event) -> Option<Self::Output> {
+        match event {
+            UserEvent::List(_) | UserEvent::Known(_) => Some(event),
+            UserEvent::Other { event } if event == "listenKeyExpired" => {
+                warn!("listen key expired, creating new key");
+                self.expire_key();
+                None
+            }
+            UserEvent::Other { event } => {
+                warn!("unhandled user stream event {event}");
+                None
+            }
+        }
     }
 
     fn handle_input(&mut self, input: Self::Input, keep_running: &mut AtomicBool) {
         match input {
             Message::NewApiKey(new_key) => {
-                self.api_key = new_key;
+                let old_key = std::mem::replace(&mut self.api_key, new_key);
+                self.retired = self
+                    .listen_key
+                    .lock()
+                    .unwrap()
+                    .take()
+                    .map(|listen_key| (old_key, listen_key));
                 keep_running.store(false, std::sync::atomic::Ordering::Relaxed);
             }
         };
     }
 }
 
-pub fn connect(api_key: String) -> Subscription<WsMessage> {
+pub fn connect(
+    api_key: String,
+    listen_key: ListenKey,
+    reconnect: Reconnect,
+) -> Subscription<WsMessage> {
     struct Connect;
 
-    subscription::channel(std::any::TypeId::of::<Connect>(), 100, |output| async {
-        UserWs::new(api_key).run(output).await
-    })
+    subscription::channel(
+        std::any::TypeId::of::<Connect>(),
+        100,
+        move |output| async move {
+            UserWs::new(api_key, listen_key)
+                .run(output, reconnect)
+                .await
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_user_events_are_kept_by_name() {
+        let expired: UserEvent =
+            serde_json::from_str(r#"{"e":"listenKeyExpired","E":1,"listenKey":"abc"}"#).unwrap();
+        assert!(matches!(expired, UserEvent::Other { event } if event == "listenKeyExpired"));
+
+        let balance: UserEvent =
+            serde_json::from_str(r#"{"e":"balanceUpdate","E":1,"a":"BTC","d":"0.5","T":2}"#)
+                .unwrap();
+        assert!(matches!(
+            balance,
+            UserEvent::Known(WebsocketEvent::BalanceUpdate(_))
+        ));
+
+        let list: UserEvent = serde_json::from_str(
+            r#"{"e":"listStatus","E":1,"s":"BTCUSDT","g":7,"c":"OCO","l":"ALL_DONE",
+                "L":"ALL_DONE","r":"NONE","C":"x","T":2,
+                "O":[{"s":"BTCUSDT","i":11,"c":"a"},{"s":"BTCUSDT","i":12,"c":"b"}]}"#,
+        )
+        .unwrap();
+        let UserEvent::List(ListEvent::Status(list)) = list else {
+            panic!("list status parsed as {list:?}");
+        };
+        assert_eq!(list.status, ListOrderStatus::AllDone);
+        assert_eq!(
+            list.orders.iter().map(|o| o.order_id).collect::<Vec<_>>(),
+            [11, 12]
+        );
+    }
 }